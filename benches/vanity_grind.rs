@@ -0,0 +1,57 @@
+//! Keys/sec of the old grinding loop versus the seed-batch grinder.
+//!
+//! Run with `cargo bench --bench vanity_grind`.
+
+use onlypump_backend::vanity_grind::{Grinder, PatternMatcher};
+use onlypump_backend::vanity_pattern::VanityPattern;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::time::{Duration, Instant};
+
+const RUN_FOR: Duration = Duration::from_secs(5);
+
+/// The loop `generate_vanity_batch` used before: full keypair plus a `String` per attempt
+fn baseline(pattern: &VanityPattern) -> (u64, usize) {
+    let mut attempts = 0u64;
+    let mut hits = 0;
+    let start = Instant::now();
+    while start.elapsed() < RUN_FOR {
+        for _ in 0..1_000 {
+            let kp = Keypair::new();
+            if pattern.matches(&kp.pubkey().to_string()) {
+                hits += 1;
+            }
+        }
+        attempts += 1_000;
+    }
+    (attempts, hits)
+}
+
+fn seed_batches(pattern: &VanityPattern) -> (u64, usize) {
+    let matcher = PatternMatcher::new(pattern);
+    let mut grinder = Grinder::new(&matcher);
+    let mut hits = Vec::new();
+    let mut attempts = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < RUN_FOR {
+        attempts += grinder.next_batch(&mut hits) as u64;
+    }
+    (attempts, hits.len())
+}
+
+fn report(name: &str, pattern: &VanityPattern, run: fn(&VanityPattern) -> (u64, usize)) -> f64 {
+    let start = Instant::now();
+    let (attempts, hits) = run(pattern);
+    let rate = attempts as f64 / start.elapsed().as_secs_f64();
+    println!("{:<14} {:<8} {:>12.0} keys/sec  ({} hits)", name, pattern.to_string(), rate, hits);
+    rate
+}
+
+fn main() {
+    println!("single thread, {:?} per run", RUN_FOR);
+    for pattern in ["pump", "ony*"] {
+        let pattern = VanityPattern::parse(pattern).expect("valid pattern");
+        let before = report("baseline", &pattern, baseline);
+        let after = report("seed batches", &pattern, seed_batches);
+        println!("{:<14} {:<8} {:>12.2}x", "speedup", pattern.to_string(), after / before);
+    }
+}
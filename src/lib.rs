@@ -0,0 +1,139 @@
+//! OnlyPump backend: the HTTP API, vanity address pools and everything they share.
+//!
+//! `main` and the tools under `src/bin` are thin wrappers around this crate, and the
+//! end-to-end tests drive the same [`build_router`] the server runs.
+
+pub mod auth;
+pub mod config;
+pub mod cors;
+pub mod creator;
+pub mod curve;
+pub mod health;
+pub mod logging;
+pub mod metrics;
+pub mod payer;
+pub mod pump;
+pub mod pump_swap;
+pub mod pump_trade;
+pub mod rate_limit;
+pub mod routes;
+pub mod rpc_pool;
+pub mod shutdown;
+pub mod trading;
+pub mod vanity;
+pub mod vanity_file;
+pub mod vanity_grind;
+pub mod vanity_ingest;
+pub mod vanity_jobs;
+pub mod vanity_pattern;
+pub mod vanity_reservations;
+
+#[cfg(test)]
+mod api_tests;
+#[cfg(test)]
+mod mock_rpc;
+
+use config::Config;
+use health::{CachedCheck, SlotTracker};
+use pumpfun::{
+    common::types::{Cluster, PriorityFee},
+    PumpFun,
+};
+use rpc_pool::RpcPool;
+use shutdown::Shutdown;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use std::sync::Arc;
+use tracing::info;
+use vanity::VanityService;
+use vanity_ingest::VanityIngest;
+use vanity_jobs::VanityJobs;
+use vanity_reservations::VanityReservations;
+
+pub use routes::build_router;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub pump_client: Arc<PumpFun>,
+    pub rpc_client: Arc<RpcClient>,
+    pub rpc_pool: RpcPool,
+    pub payer: Pubkey,
+    /// Signs prepared transactions as fee payer; everything else goes through `pump_client`
+    pub payer_signer: Arc<Keypair>,
+    pub vanity_service: Arc<VanityService>,
+    pub vanity_jobs: Arc<VanityJobs>,
+    pub vanity_ingest: Arc<VanityIngest>,
+    pub vanity_reservations: Arc<VanityReservations>,
+    pub shutdown: Shutdown,
+    pub slot_tracker: Arc<SlotTracker>,
+    pub websocket_check: Arc<CachedCheck>,
+}
+
+/// Connect the RPC pool, PumpFun client and vanity pools for `config`
+pub async fn build_state(config: Arc<Config>, payer: Arc<Keypair>) -> Result<AppState, String> {
+    let payer_pubkey = payer.pubkey();
+    let payer_signer = payer.clone();
+    
+    // All RPC traffic goes through the endpoint pool so a throttled provider fails over
+    let rpc_pool = RpcPool::new(config.rpc_endpoints.clone());
+    rpc_pool.spawn_health_checks(std::time::Duration::from_secs(15));
+    let rpc_client = Arc::new(rpc_pool.client(CommitmentConfig::confirmed()));
+    
+    // Create PumpFun client; websocket subscriptions use the primary endpoint
+    let cluster_config = Cluster::new(
+        rpc_pool.primary().url.clone(),
+        rpc_pool.primary().ws_url.clone(),
+        CommitmentConfig::confirmed(),
+        PriorityFee::default()
+    );
+    
+    let mut pump_client = PumpFun::new(payer, cluster_config);
+    pump_client.rpc = rpc_client.clone();
+    let pump_client = Arc::new(pump_client);
+    info!("PumpFun client initialized for cluster: {}", config.cluster);
+    
+    // Initialize vanity pools
+    let vanity_service = VanityService::from_specs(
+        config.vanity.pools.clone(),
+        config.vanity.default_pool.clone(),
+        config.vanity.low_water_mark,
+        config.vanity.grind_threads,
+        config.vanity.refill_from_file,
+    )
+    .await
+    .map_err(|e| format!("failed to initialize vanity pools: {}", e))?;
+    
+    // Custom vanity jobs share the CPU budget with the pools, so keep them small by default
+    let vanity_jobs = Arc::new(VanityJobs::new(config.jobs));
+    vanity_jobs.spawn_pruning(std::time::Duration::from_secs(60));
+    
+    // Addresses previewed in the launch modal go back to their pool if never used
+    let vanity_service = Arc::new(vanity_service);
+    let vanity_reservations = Arc::new(VanityReservations::new(vanity_service.clone(), config.reservations));
+    vanity_reservations.spawn_expiry(std::time::Duration::from_secs(10));
+    
+    // Remote grinders top up the same pools through /vanity/ingest
+    let vanity_ingest = VanityIngest::new(config.vanity.workers.clone());
+    
+    Ok(AppState {
+        config,
+        pump_client,
+        rpc_client,
+        rpc_pool,
+        payer: payer_pubkey,
+        payer_signer,
+        vanity_service,
+        vanity_jobs,
+        vanity_ingest: Arc::new(vanity_ingest),
+        vanity_reservations,
+        shutdown: Shutdown::new(),
+        slot_tracker: Arc::new(SlotTracker::default()),
+        websocket_check: Arc::new(CachedCheck::default()),
+    })
+}
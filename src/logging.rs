@@ -0,0 +1,181 @@
+use axum::{extract::MatchedPath, http::Request};
+use ed25519_dalek::{PublicKey, SecretKey};
+use regex::Regex;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+use tracing::Span;
+use tracing_subscriber::{fmt::MakeWriter, EnvFilter};
+
+const REDACTED: &str = "[redacted]";
+
+/// `key=value`, `"key":"value"` and Debug-style `key: "value"` for fields that must never be
+/// logged. Bare `key: value` only for the signature header, so prose like
+/// "Invalid signature: bad format" is left alone.
+static SENSITIVE_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(\b(?:pump_private_key|private_key|secret_key|secret|signature|passphrase)(?:"\s*:\s*"|\s*=\s*"?|:\s*")|\bx-request-signature"?\s*[:=]\s*"?)([^"\s,}&]+)"#,
+    )
+    .expect("valid regex")
+});
+
+/// Base58 strings long enough to hold 64 bytes
+static BASE58_BLOB: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[1-9A-HJ-NP-Za-km-z]{80,90}\b").expect("valid regex"));
+
+/// Install the global subscriber. `LOG_FORMAT=json` switches to one JSON object per line.
+///
+/// Reads the environment directly because logging has to be up before [`crate::config::Config`]
+/// is loaded, so configuration errors can be reported.
+pub fn init() {
+    let filter = EnvFilter::from_default_env();
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json"));
+
+    if json {
+        tracing_subscriber::fmt()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_env_filter(filter)
+            .with_writer(RedactingStdout)
+            .init();
+    } else {
+        // Colour codes between field names and values would defeat the redaction patterns
+        tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_env_filter(filter)
+            .with_writer(RedactingStdout)
+            .init();
+    }
+}
+
+/// Span wrapping each request; handlers fill in `wallet` and `mint` once they know them
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or_else(|| request.uri().path());
+
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        route = %route,
+        wallet = tracing::field::Empty,
+        mint = tracing::field::Empty,
+    )
+}
+
+pub fn record_wallet(wallet: &str) {
+    Span::current().record("wallet", wallet);
+}
+
+pub fn record_mint(mint: &impl std::fmt::Display) {
+    Span::current().record("mint", tracing::field::display(mint));
+}
+
+/// Scrub sensitive field values and anything that looks like a Solana secret key
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let line = SENSITIVE_FIELD.replace_all(line, |caps: &regex::Captures| format!("{}{}", &caps[1], REDACTED));
+    if !BASE58_BLOB.is_match(&line) {
+        return line;
+    }
+    let scrubbed = BASE58_BLOB.replace_all(&line, |caps: &regex::Captures| {
+        if is_secret_keypair(&caps[0]) {
+            REDACTED.to_string()
+        } else {
+            caps[0].to_string()
+        }
+    });
+    Cow::Owned(scrubbed.into_owned())
+}
+
+/// A 64-byte keypair is a seed followed by its own public key; transaction signatures
+/// have the same length but never satisfy that, so they stay readable
+fn is_secret_keypair(candidate: &str) -> bool {
+    let Ok(bytes) = bs58::decode(candidate).into_vec() else {
+        return false;
+    };
+    if bytes.len() != 64 {
+        return false;
+    }
+    SecretKey::from_bytes(&bytes[..32])
+        .map(|secret| PublicKey::from(&secret).as_bytes()[..] == bytes[32..])
+        .unwrap_or(false)
+}
+
+/// stdout, with every formatted event passed through [`redact`]
+pub struct RedactingStdout;
+
+impl<'a> MakeWriter<'a> for RedactingStdout {
+    type Writer = RedactingWriter<io::Stdout>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(io::stdout())
+    }
+}
+
+pub struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    // The fmt layer hands over each event as a single buffer
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    #[test]
+    fn test_redacts_sensitive_fields() {
+        let line = r#"body={"pump_private_key":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","signature":"5Kd3NBU","wallet_address":"Abc"}"#;
+        let redacted = redact(line);
+        assert!(!redacted.contains("4vJ9JU1b"));
+        assert!(!redacted.contains("5Kd3NBU"));
+        assert!(redacted.contains(r#""wallet_address":"Abc""#));
+
+        assert_eq!(redact("signature=abc123 ok"), "signature=[redacted] ok");
+        assert_eq!(redact("x-request-signature: abc"), "x-request-signature: [redacted]");
+        assert_eq!(redact(r#"CreateTokenRequest { signature: "abc" }"#), r#"CreateTokenRequest { signature: "[redacted]" }"#);
+        let prose = "Invalid wallet signature: Invalid signature format";
+        assert_eq!(redact(prose), prose);
+    }
+
+    #[test]
+    fn test_redacts_secret_keys_but_not_transaction_signatures() {
+        let keypair = Keypair::new();
+        let secret = keypair.to_base58_string();
+        let tx_signature = keypair.sign_message(b"tx").to_string();
+
+        let line = format!("decoded {} for mint {}", secret, keypair.pubkey());
+        let redacted = redact(&line);
+        assert!(!redacted.contains(&secret));
+        assert!(redacted.contains(&keypair.pubkey().to_string()));
+
+        let line = format!("Token created successfully: {}", tx_signature);
+        assert_eq!(redact(&line), line);
+    }
+
+    #[test]
+    fn test_writer_redacts_output() {
+        let keypair = Keypair::new();
+        let mut writer = RedactingWriter(Vec::new());
+        write!(writer, "leaked {}", keypair.to_base58_string()).unwrap();
+        assert_eq!(String::from_utf8(writer.0).unwrap(), "leaked [redacted]");
+    }
+}
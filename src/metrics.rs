@@ -0,0 +1,256 @@
+//! Prometheus metrics served on `GET /metrics`.
+//!
+//! Rates are left to PromQL: vanity keys/sec is
+//! `rate(onlypump_vanity_keys_attempted_total[1m])` and hits/min is
+//! `increase(onlypump_vanity_keys_found_total[1m])`.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::fmt::Display;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Process-wide metrics, shared by handlers, the RPC pool and the vanity grinders
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+pub struct Metrics {
+    registry: Registry,
+    /// Labels: route, method, status
+    pub http_requests: IntCounterVec,
+    /// Labels: route, method
+    pub http_latency: HistogramVec,
+    /// Labels: kind (create, create_and_buy, buy, sell), outcome (success or error class)
+    pub trades: IntCounterVec,
+    /// Transactions submitted and not yet confirmed or failed
+    pub pending_transactions: IntGauge,
+    /// Labels: endpoint, method
+    pub rpc_latency: HistogramVec,
+    /// Labels: endpoint, kind
+    pub rpc_errors: IntCounterVec,
+    /// Labels: pool, pattern
+    pub vanity_pool_size: IntGaugeVec,
+    /// Labels: pool, pattern
+    pub vanity_pool_target: IntGaugeVec,
+    /// Labels: pattern
+    pub vanity_keys_attempted: IntCounterVec,
+    /// Labels: pattern
+    pub vanity_keys_found: IntCounterVec,
+    /// Labels: worker, outcome (accepted, duplicate, invalid, surplus)
+    pub vanity_keys_ingested: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("onlypump".to_string()), None).expect("valid registry prefix");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["route", "method", "status"],
+        )
+        .unwrap();
+        let http_latency = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route"),
+            &["route", "method"],
+        )
+        .unwrap();
+        let trades = IntCounterVec::new(
+            Opts::new("trades_total", "Trade attempts by kind and outcome"),
+            &["kind", "outcome"],
+        )
+        .unwrap();
+        let pending_transactions =
+            IntGauge::new("pending_transactions", "Transactions submitted and awaiting confirmation").unwrap();
+        let rpc_latency = HistogramVec::new(
+            HistogramOpts::new("rpc_request_duration_seconds", "Solana JSON-RPC latency by endpoint and method")
+                .buckets(vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+            &["endpoint", "method"],
+        )
+        .unwrap();
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("rpc_errors_total", "Solana JSON-RPC failures by endpoint and kind"),
+            &["endpoint", "kind"],
+        )
+        .unwrap();
+        let vanity_pool_size = IntGaugeVec::new(
+            Opts::new("vanity_pool_size", "Vanity keypairs ready to hand out"),
+            &["pool", "pattern"],
+        )
+        .unwrap();
+        let vanity_pool_target = IntGaugeVec::new(
+            Opts::new("vanity_pool_target_size", "Size each vanity pool is refilled to"),
+            &["pool", "pattern"],
+        )
+        .unwrap();
+        let vanity_keys_attempted = IntCounterVec::new(
+            Opts::new("vanity_keys_attempted_total", "Keys ground while refilling vanity pools"),
+            &["pattern"],
+        )
+        .unwrap();
+        let vanity_keys_found = IntCounterVec::new(
+            Opts::new("vanity_keys_found_total", "Matching keys found while refilling vanity pools"),
+            &["pattern"],
+        )
+        .unwrap();
+        let vanity_keys_ingested = IntCounterVec::new(
+            Opts::new("vanity_keys_ingested_total", "Keypairs pushed by remote vanity workers"),
+            &["worker", "outcome"],
+        )
+        .unwrap();
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_latency.clone()),
+            Box::new(trades.clone()),
+            Box::new(pending_transactions.clone()),
+            Box::new(rpc_latency.clone()),
+            Box::new(rpc_errors.clone()),
+            Box::new(vanity_pool_size.clone()),
+            Box::new(vanity_pool_target.clone()),
+            Box::new(vanity_keys_attempted.clone()),
+            Box::new(vanity_keys_found.clone()),
+            Box::new(vanity_keys_ingested.clone()),
+        ] {
+            registry.register(collector).expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_latency,
+            trades,
+            pending_transactions,
+            rpc_latency,
+            rpc_errors,
+            vanity_pool_size,
+            vanity_pool_target,
+            vanity_keys_attempted,
+            vanity_keys_found,
+            vanity_keys_ingested,
+        }
+    }
+
+    /// Text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding never fails");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+
+    /// Count a trade attempt, bucketing failures by [`trade_error_class`]
+    pub fn record_trade<T, E: Display>(&self, kind: &str, result: &Result<T, E>) {
+        let outcome = match result {
+            Ok(_) => "success",
+            Err(e) => trade_error_class(&e.to_string()),
+        };
+        self.trades.with_label_values(&[kind, outcome]).inc();
+    }
+
+    /// Mark a transaction as in flight until the returned guard is dropped
+    pub fn pending_transaction(&self) -> PendingGuard {
+        self.pending_transactions.inc();
+        PendingGuard(self.pending_transactions.clone())
+    }
+}
+
+pub struct PendingGuard(IntGauge);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Coarse error class from an SDK error message, kept to a fixed set of label values
+pub fn trade_error_class(message: &str) -> &'static str {
+    let message = message.to_ascii_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+
+    if has(&["insufficient"]) {
+        "insufficient_funds"
+    } else if has(&["slippage", "toomuchsolrequired", "toolittlesolreceived", "0x1772", "0x1773"]) {
+        "slippage"
+    } else if has(&["blockhash"]) {
+        "blockhash_expired"
+    } else if has(&["429", "too many requests", "rate limit"]) {
+        "rate_limited"
+    } else if has(&["timed out", "timeout"]) {
+        "timeout"
+    } else if has(&["bonding curve", "bondingcurve"]) {
+        "bonding_curve"
+    } else if has(&["simulat", "preflight"]) {
+        "simulation_failed"
+    } else if has(&["metadata", "ipfs", "upload"]) {
+        "metadata_upload"
+    } else if has(&["rpc", "connection", "error sending request"]) {
+        "rpc"
+    } else {
+        "other"
+    }
+}
+
+/// Axum middleware counting requests and timing them per matched route
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let metrics = metrics();
+    metrics
+        .http_latency
+        .with_label_values(&[&route, &method])
+        .observe(start.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .with_label_values(&[&route, &method, response.status().as_str()])
+        .inc();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_error_classes() {
+        assert_eq!(trade_error_class("Transaction simulation failed: Error processing Instruction 2: custom program error: 0x1772"), "slippage");
+        assert_eq!(trade_error_class("Attempt to debit an account but found no record of a prior credit. insufficient lamports"), "insufficient_funds");
+        assert_eq!(trade_error_class("Blockhash not found"), "blockhash_expired");
+        assert_eq!(trade_error_class("HTTP status client error (429 Too Many Requests)"), "rate_limited");
+        assert_eq!(trade_error_class("Bonding curve account not found"), "bonding_curve");
+        assert_eq!(trade_error_class("something unexpected"), "other");
+    }
+
+    #[test]
+    fn test_render_includes_recorded_values() {
+        let metrics = metrics();
+        metrics.record_trade::<(), _>("buy", &Err("Blockhash not found"));
+        {
+            let _pending = metrics.pending_transaction();
+            assert!(metrics.pending_transactions.get() >= 1);
+        }
+        metrics.vanity_keys_attempted.with_label_values(&["pump"]).inc_by(4096);
+
+        let text = metrics.render();
+        assert!(text.contains(r#"onlypump_trades_total{kind="buy",outcome="blockhash_expired"}"#));
+        assert!(text.contains("onlypump_pending_transactions"));
+        assert!(text.contains(r#"onlypump_vanity_keys_attempted_total{pattern="pump"}"#));
+    }
+}
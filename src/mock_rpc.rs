@@ -0,0 +1,563 @@
+//! In-process stand-in for a Solana validator's JSON-RPC and PubSub endpoints, so
+//! handlers can be driven end to end without network access.
+//!
+//! Accounts are whatever a test registers; unknown accounts read as missing. Every
+//! `sendTransaction` is recorded and its signature reported as confirmed, unless
+//! the test asked sends to fail.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::post,
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub const PUMP_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+pub const PUMP_SWAP_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+/// Anchor account discriminators from the pump program IDL
+const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+const GLOBAL_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+
+/// Anchor account discriminators from the PumpSwap IDL
+const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+const GLOBAL_CONFIG_DISCRIMINATOR: [u8; 8] = [149, 8, 156, 202, 160, 252, 176, 217];
+
+#[derive(Clone, Debug)]
+pub struct MockAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SentTransaction {
+    pub signature: String,
+    /// Wire-format transaction as submitted
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct MockState {
+    accounts: Mutex<HashMap<Pubkey, MockAccount>>,
+    sent: Mutex<Vec<SentTransaction>>,
+    calls: Mutex<HashMap<String, usize>>,
+    /// Preflight error returned for every `sendTransaction` while set
+    send_error: Mutex<Option<String>>,
+    slot: AtomicU64,
+    blockhash: Hash,
+}
+
+pub struct MockRpc {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+}
+
+impl MockRpc {
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState {
+            slot: AtomicU64::new(300_000_000),
+            blockhash: Hash::new_unique(),
+            ..MockState::default()
+        });
+        let app = Router::new().route("/", post(rpc).get(pubsub)).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    pub fn set_account(&self, pubkey: Pubkey, account: MockAccount) {
+        self.state.accounts.lock().unwrap().insert(pubkey, account);
+    }
+
+    /// A system-owned account holding `lamports`
+    pub fn set_balance(&self, pubkey: Pubkey, lamports: u64) {
+        self.set_account(
+            pubkey,
+            MockAccount {
+                lamports,
+                owner: solana_system_interface::program::id(),
+                data: Vec::new(),
+            },
+        );
+    }
+
+    /// A mint account owned by `token_program`; only its owner is ever read
+    pub fn set_mint(&self, mint: &Pubkey, token_program: Pubkey) {
+        self.set_account(
+            *mint,
+            MockAccount {
+                lamports: 1_461_600,
+                owner: token_program,
+                data: vec![0; 82],
+            },
+        );
+    }
+
+    /// Register the pump global account, an SPL Token `mint` and its bonding curve
+    pub fn set_pump_curve(&self, mint: &Pubkey, curve: &BondingCurve) {
+        let (global, _) = Pubkey::find_program_address(&[b"global"], &PUMP_PROGRAM_ID);
+        let (bonding_curve, _) = Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PUMP_PROGRAM_ID);
+        let owned = |data| MockAccount {
+            lamports: 1_461_600,
+            owner: PUMP_PROGRAM_ID,
+            data,
+        };
+        self.set_account(global, owned(global_account_data()));
+        self.set_account(bonding_curve, owned(curve.to_account_data()));
+        self.set_mint(mint, spl_token::id());
+    }
+
+    /// Register PumpSwap's global config, `pool` and its two token vaults
+    pub fn set_swap_pool(&self, pool: &SwapPool) {
+        let (global_config, _) = Pubkey::find_program_address(&[b"global_config"], &PUMP_SWAP_PROGRAM_ID);
+        let owned = |owner, data| MockAccount {
+            lamports: 2_039_280,
+            owner,
+            data,
+        };
+        self.set_account(global_config, owned(PUMP_SWAP_PROGRAM_ID, swap_global_config_data()));
+        self.set_account(pool.address, owned(PUMP_SWAP_PROGRAM_ID, pool.to_account_data()));
+        self.set_account(pool.base_vault(), owned(pool.base_program, pool.base_vault_data()));
+        self.set_account(pool.quote_vault(), owned(spl_token::id(), pool.quote_vault_data()));
+    }
+
+    /// An SPL Token account at `address` holding `amount` of `mint` for `owner`
+    pub fn set_token_account(&self, address: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        self.set_account(
+            address,
+            MockAccount {
+                lamports: 2_039_280,
+                owner: spl_token::id(),
+                data: token_account_data(mint, owner, amount),
+            },
+        );
+    }
+
+    /// Make every following `sendTransaction` fail preflight with `message`
+    pub fn fail_sends(&self, message: &str) {
+        *self.state.send_error.lock().unwrap() = Some(message.to_string());
+    }
+
+    pub fn sent_transactions(&self) -> Vec<SentTransaction> {
+        self.state.sent.lock().unwrap().clone()
+    }
+
+    /// How many times `method` was called over HTTP
+    pub fn calls(&self, method: &str) -> usize {
+        self.state.calls.lock().unwrap().get(method).copied().unwrap_or(0)
+    }
+}
+
+/// Bonding curve reserves, laid out as the pump program stores them
+#[derive(Clone, Debug)]
+pub struct BondingCurve {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    pub creator: Pubkey,
+}
+
+impl Default for BondingCurve {
+    /// A freshly launched curve
+    fn default() -> Self {
+        Self {
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: Pubkey::new_unique(),
+        }
+    }
+}
+
+impl BondingCurve {
+    pub fn to_account_data(&self) -> Vec<u8> {
+        let mut data = BONDING_CURVE_DISCRIMINATOR.to_vec();
+        for value in [
+            self.virtual_token_reserves,
+            self.virtual_sol_reserves,
+            self.real_token_reserves,
+            self.real_sol_reserves,
+            self.token_total_supply,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(self.complete as u8);
+        data.extend_from_slice(self.creator.as_ref());
+        data
+    }
+}
+
+/// Global config with mainnet's launch parameters and a 1% fee
+pub fn global_account_data() -> Vec<u8> {
+    let fee_recipient = Pubkey::new_unique();
+    let mut data = GLOBAL_DISCRIMINATOR.to_vec();
+    data.push(1); // initialized
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // authority
+    data.extend_from_slice(fee_recipient.as_ref());
+    for value in [
+        1_073_000_000_000_000u64, // initial_virtual_token_reserves
+        30_000_000_000,           // initial_virtual_sol_reserves
+        793_100_000_000_000,      // initial_real_token_reserves
+        1_000_000_000_000_000,    // token_total_supply
+        100,                      // fee_basis_points
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // withdraw_authority
+    data.push(1); // enable_migrate
+    data.extend_from_slice(&15_000_001u64.to_le_bytes()); // pool_migration_fee
+    data.extend_from_slice(&5u64.to_le_bytes()); // creator_fee_basis_points
+    for _ in 0..7 {
+        data.extend_from_slice(fee_recipient.as_ref()); // fee_recipients
+    }
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // set_creator_authority
+    data
+}
+
+/// Address of the pool a completed curve for `mint` migrates into
+pub fn swap_pool_address(mint: &Pubkey) -> Pubkey {
+    let (pool_authority, _) = Pubkey::find_program_address(&[b"pool-authority", mint.as_ref()], &PUMP_PROGRAM_ID);
+    let seeds: [&[u8]; 5] = [
+        b"pool",
+        &[0, 0], // index
+        pool_authority.as_ref(),
+        mint.as_ref(),
+        spl_token::native_mint::ID.as_ref(),
+    ];
+    Pubkey::find_program_address(&seeds, &PUMP_SWAP_PROGRAM_ID).0
+}
+
+/// A graduated token's PumpSwap pool, laid out as the AMM stores it
+#[derive(Clone, Debug)]
+pub struct SwapPool {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    /// Token program of `mint`
+    pub base_program: Pubkey,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub coin_creator: Pubkey,
+}
+
+impl SwapPool {
+    /// A pool right after migration: the curve's last ~207M tokens against ~85 SOL
+    pub fn new(mint: &Pubkey, base_program: Pubkey, coin_creator: Pubkey) -> Self {
+        Self {
+            address: swap_pool_address(mint),
+            mint: *mint,
+            base_program,
+            base_reserve: 206_900_000_000_000,
+            quote_reserve: 85_000_000_000,
+            coin_creator,
+        }
+    }
+
+    pub fn base_vault(&self) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &self.address,
+            &self.mint,
+            &self.base_program,
+        )
+    }
+
+    pub fn quote_vault(&self) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address(&self.address, &spl_token::native_mint::id())
+    }
+
+    pub fn to_account_data(&self) -> Vec<u8> {
+        let (pool_authority, _) = Pubkey::find_program_address(&[b"pool-authority", self.mint.as_ref()], &PUMP_PROGRAM_ID);
+        let mut data = POOL_DISCRIMINATOR.to_vec();
+        data.push(255); // pool_bump
+        data.extend_from_slice(&0u16.to_le_bytes()); // index
+        for key in [
+            pool_authority, // creator
+            self.mint,
+            spl_token::native_mint::id(),
+            Pubkey::new_unique(), // lp_mint
+            self.base_vault(),
+            self.quote_vault(),
+        ] {
+            data.extend_from_slice(key.as_ref());
+        }
+        data.extend_from_slice(&1_000_000u64.to_le_bytes()); // lp_supply
+        data.extend_from_slice(self.coin_creator.as_ref());
+        data
+    }
+
+    pub fn base_vault_data(&self) -> Vec<u8> {
+        token_account_data(&self.mint, &self.address, self.base_reserve)
+    }
+
+    pub fn quote_vault_data(&self) -> Vec<u8> {
+        token_account_data(&spl_token::native_mint::id(), &self.address, self.quote_reserve)
+    }
+}
+
+/// PumpSwap's config with mainnet's 0.2% LP, 0.05% protocol and 0.05% creator fees
+pub fn swap_global_config_data() -> Vec<u8> {
+    let mut data = GLOBAL_CONFIG_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // admin
+    data.extend_from_slice(&20u64.to_le_bytes()); // lp_fee_basis_points
+    data.extend_from_slice(&5u64.to_le_bytes()); // protocol_fee_basis_points
+    data.push(0); // disable_flags
+    for _ in 0..8 {
+        data.extend_from_slice(Pubkey::new_unique().as_ref()); // protocol_fee_recipients
+    }
+    data.extend_from_slice(&5u64.to_le_bytes()); // coin_creator_fee_basis_points
+    data
+}
+
+/// An initialized token account holding `amount` of `mint`
+fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(165);
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.resize(108, 0); // delegate
+    data.push(1); // state: initialized
+    data.resize(165, 0);
+    data
+}
+
+async fn rpc(State(state): State<Arc<MockState>>, Json(request): Json<Value>) -> Json<Value> {
+    // The client batches nothing, but accept arrays anyway
+    if let Value::Array(requests) = request {
+        let responses: Vec<Value> = requests.into_iter().map(|r| handle(&state, r)).collect();
+        return Json(Value::Array(responses));
+    }
+    Json(handle(&state, request))
+}
+
+fn handle(state: &MockState, request: Value) -> Value {
+    let id = request["id"].clone();
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let params = request["params"].clone();
+    *state.calls.lock().unwrap().entry(method.clone()).or_default() += 1;
+
+    let slot = state.slot.fetch_add(1, Ordering::Relaxed);
+    let context = json!({ "slot": slot, "apiVersion": "2.3.0" });
+    let result = match method.as_str() {
+        "getHealth" => json!("ok"),
+        "getVersion" => json!({ "solana-core": "2.3.0", "feature-set": 0 }),
+        "getSlot" => json!(slot),
+        "getBlockHeight" => json!(slot - 1_000),
+        "getEpochInfo" => json!({
+            "absoluteSlot": slot,
+            "blockHeight": slot - 1_000,
+            "epoch": 700,
+            "slotIndex": slot % 432_000,
+            "slotsInEpoch": 432_000,
+            "transactionCount": null
+        }),
+        "getLatestBlockhash" => json!({
+            "context": context,
+            "value": { "blockhash": state.blockhash.to_string(), "lastValidBlockHeight": slot + 150 }
+        }),
+        "isBlockhashValid" => json!({ "context": context, "value": true }),
+        "getFeeForMessage" => json!({ "context": context, "value": 5_000 }),
+        "getMinimumBalanceForRentExemption" => json!(2_039_280),
+        "getBalance" => {
+            let lamports = pubkey_param(&params[0]).and_then(|key| account(state, &key)).map_or(0, |a| a.lamports);
+            json!({ "context": context, "value": lamports })
+        }
+        "getAccountInfo" => {
+            let value = pubkey_param(&params[0]).and_then(|key| account(state, &key)).map(|a| account_json(&a));
+            json!({ "context": context, "value": value })
+        }
+        "getTokenAccountBalance" => {
+            let data = pubkey_param(&params[0]).and_then(|key| account(state, &key)).map(|a| a.data);
+            let Some(amount) = data.and_then(|d| d.get(64..72).map(|b| u64::from_le_bytes(b.try_into().unwrap()))) else {
+                return error(id, -32602, "Invalid param: could not find account");
+            };
+            // Pump mints all use 6 decimals
+            let ui_amount = amount as f64 / 1e6;
+            json!({
+                "context": context,
+                "value": {
+                    "amount": amount.to_string(),
+                    "decimals": 6,
+                    "uiAmount": ui_amount,
+                    "uiAmountString": ui_amount.to_string()
+                }
+            })
+        }
+        "getMultipleAccounts" => {
+            let keys = params[0].as_array().cloned().unwrap_or_default();
+            let values: Vec<Value> = keys
+                .iter()
+                .map(|key| {
+                    pubkey_param(key)
+                        .and_then(|key| account(state, &key))
+                        .map_or(Value::Null, |a| account_json(&a))
+                })
+                .collect();
+            json!({ "context": context, "value": values })
+        }
+        "simulateTransaction" => json!({
+            "context": context,
+            "value": { "err": null, "logs": [], "accounts": null, "unitsConsumed": 50_000, "returnData": null }
+        }),
+        "sendTransaction" => {
+            let bytes = params[0].as_str().and_then(|tx| BASE64.decode(tx).ok()).unwrap_or_default();
+            let Some(signature) = first_signature(&bytes) else {
+                return error(id, -32602, "invalid transaction: failed to deserialize");
+            };
+            if let Some(message) = state.send_error.lock().unwrap().clone() {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": -32002,
+                        "message": message,
+                        "data": { "err": { "InstructionError": [2, { "Custom": 6002 }] }, "logs": [], "accounts": null, "unitsConsumed": 0, "returnData": null }
+                    },
+                    "id": id
+                });
+            }
+            state.sent.lock().unwrap().push(SentTransaction {
+                signature: signature.clone(),
+                bytes,
+            });
+            json!(signature)
+        }
+        "getSignatureStatuses" => {
+            let sent = state.sent.lock().unwrap();
+            let statuses: Vec<Value> = params[0]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .map(|signature| {
+                    if sent.iter().any(|tx| Some(tx.signature.as_str()) == signature.as_str()) {
+                        json!({
+                            "slot": slot,
+                            "confirmations": null,
+                            "err": null,
+                            "status": { "Ok": null },
+                            "confirmationStatus": "finalized"
+                        })
+                    } else {
+                        Value::Null
+                    }
+                })
+                .collect();
+            json!({ "context": context, "value": statuses })
+        }
+        _ => return error(id, -32601, "Method not found"),
+    };
+    json!({ "jsonrpc": "2.0", "result": result, "id": id })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
+
+fn pubkey_param(value: &Value) -> Option<Pubkey> {
+    value.as_str()?.parse().ok()
+}
+
+fn account(state: &MockState, pubkey: &Pubkey) -> Option<MockAccount> {
+    state.accounts.lock().unwrap().get(pubkey).cloned()
+}
+
+fn account_json(account: &MockAccount) -> Value {
+    json!({
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "data": [BASE64.encode(&account.data), "base64"],
+        "executable": false,
+        "rentEpoch": u64::MAX,
+        "space": account.data.len()
+    })
+}
+
+/// The first signature of a wire-format transaction: a compact-u16 count, then 64-byte signatures
+fn first_signature(bytes: &[u8]) -> Option<String> {
+    let mut count = 0usize;
+    let mut offset = 0;
+    for (i, byte) in bytes.iter().take(3).enumerate() {
+        count |= ((byte & 0x7f) as usize) << (7 * i);
+        offset = i + 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    let signature = bytes.get(offset..offset + 64)?;
+    Some(bs58::encode(signature).into_string())
+}
+
+async fn pubsub(State(state): State<Arc<MockState>>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| subscriptions(state, socket))
+}
+
+/// Acknowledge each subscription and push one notification for it
+async fn subscriptions(state: Arc<MockState>, mut socket: WebSocket) {
+    let mut next_subscription = 1u64;
+    while let Some(Ok(message)) = socket.recv().await {
+        let Message::Text(text) = message else { continue };
+        let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+        let id = request["id"].clone();
+        let method = request["method"].as_str().unwrap_or_default();
+        let slot = state.slot.fetch_add(1, Ordering::Relaxed);
+
+        let (result, notification) = match method {
+            "slotSubscribe" => (
+                json!(next_subscription),
+                Some(("slotNotification", json!({ "parent": slot - 1, "root": slot - 32, "slot": slot }))),
+            ),
+            "signatureSubscribe" => (
+                json!(next_subscription),
+                Some(("signatureNotification", json!({ "context": { "slot": slot }, "value": { "err": null } }))),
+            ),
+            m if m.ends_with("Subscribe") => (json!(next_subscription), None),
+            m if m.ends_with("Unsubscribe") => (json!(true), None),
+            _ => continue,
+        };
+
+        let ack = json!({ "jsonrpc": "2.0", "result": result, "id": id });
+        if socket.send(Message::Text(ack.to_string())).await.is_err() {
+            return;
+        }
+        if let Some((method, payload)) = notification {
+            let push = json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": { "result": payload, "subscription": next_subscription }
+            });
+            if socket.send(Message::Text(push.to_string())).await.is_err() {
+                return;
+            }
+        }
+        if method.ends_with("Subscribe") {
+            next_subscription += 1;
+        }
+    }
+}
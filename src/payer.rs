@@ -0,0 +1,222 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{signature::Keypair, signer::keypair::read_keypair_file, signer::Signer};
+use std::path::PathBuf;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Where the server's fee payer keypair comes from
+#[derive(Clone, Debug)]
+pub enum PayerSource {
+    /// Solana CLI JSON keypair file (`[12, 34, ...]`)
+    KeypairFile(PathBuf),
+    /// Base58-encoded 64-byte secret held in the named environment variable
+    Base58Env(String),
+    /// Passphrase-encrypted keystore file; the passphrase is read from the named environment variable
+    Keystore { path: PathBuf, passphrase_env: String },
+}
+
+#[derive(Debug, Error)]
+pub enum PayerError {
+    #[error("no payer configured; set PAYER_KEYPAIR_PATH, PAYER_SECRET_KEY or PAYER_KEYSTORE")]
+    Missing,
+    #[error("failed to read keypair file {path}: {reason}")]
+    KeypairFile { path: String, reason: String },
+    #[error("environment variable {0} is not set")]
+    MissingEnv(String),
+    #[error("payer secret is not a valid base58 keypair")]
+    InvalidSecret,
+    #[error("failed to read keystore: {0}")]
+    Keystore(String),
+    #[error("keystore passphrase is wrong or the keystore is corrupted")]
+    Decrypt,
+}
+
+/// Encrypted keypair at rest: scrypt-derived key, AES-256-GCM over the 64-byte secret
+#[derive(Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u8,
+    pub pubkey: String,
+    pub kdf: KdfParams,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+const KEYSTORE_VERSION: u8 = 1;
+
+impl Keystore {
+    /// Encrypt `keypair` under `passphrase`
+    pub fn encrypt(keypair: &Keypair, passphrase: &str) -> Result<Self, PayerError> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let kdf = KdfParams {
+            log_n: 15,
+            r: 8,
+            p: 1,
+            salt: BASE64.encode(salt),
+        };
+        let cipher = cipher_for(passphrase, &kdf, &salt)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), keypair.to_bytes().as_slice())
+            .map_err(|_| PayerError::Keystore("encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: keypair.pubkey().to_string(),
+            kdf,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the keypair and check it matches the recorded public key
+    pub fn decrypt(&self, passphrase: &str) -> Result<Keypair, PayerError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(PayerError::Keystore(format!("unsupported keystore version {}", self.version)));
+        }
+        let salt = decode_field(&self.kdf.salt, "salt")?;
+        let nonce = decode_field(&self.nonce, "nonce")?;
+        let ciphertext = decode_field(&self.ciphertext, "ciphertext")?;
+        if nonce.len() != 12 {
+            return Err(PayerError::Keystore("nonce must be 12 bytes".to_string()));
+        }
+
+        let cipher = cipher_for(passphrase, &self.kdf, &salt)?;
+        let secret = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| PayerError::Decrypt)?;
+        let keypair = Keypair::try_from(secret.as_slice()).map_err(|_| PayerError::Decrypt)?;
+
+        if keypair.pubkey().to_string() != self.pubkey {
+            return Err(PayerError::Keystore("decrypted key does not match the keystore pubkey".to_string()));
+        }
+        Ok(keypair)
+    }
+}
+
+fn cipher_for(passphrase: &str, kdf: &KdfParams, salt: &[u8]) -> Result<Aes256Gcm, PayerError> {
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+        .map_err(|e| PayerError::Keystore(format!("invalid scrypt parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| PayerError::Keystore(format!("key derivation failed: {}", e)))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|_| PayerError::Keystore("invalid key length".to_string()))
+}
+
+fn decode_field(value: &str, name: &str) -> Result<Vec<u8>, PayerError> {
+    BASE64
+        .decode(value)
+        .map_err(|_| PayerError::Keystore(format!("{} is not valid base64", name)))
+}
+
+impl PayerSource {
+    pub fn load(&self) -> Result<Keypair, PayerError> {
+        match self {
+            PayerSource::KeypairFile(path) => read_keypair_file(path).map_err(|e| PayerError::KeypairFile {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            }),
+            PayerSource::Base58Env(var) => {
+                let secret = std::env::var(var).map_err(|_| PayerError::MissingEnv(var.clone()))?;
+                let bytes = bs58::decode(secret.trim()).into_vec().map_err(|_| PayerError::InvalidSecret)?;
+                Keypair::try_from(bytes.as_slice()).map_err(|_| PayerError::InvalidSecret)
+            }
+            PayerSource::Keystore { path, passphrase_env } => {
+                let passphrase =
+                    std::env::var(passphrase_env).map_err(|_| PayerError::MissingEnv(passphrase_env.clone()))?;
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| PayerError::Keystore(format!("{}: {}", path.display(), e)))?;
+                let keystore: Keystore =
+                    serde_json::from_str(&content).map_err(|e| PayerError::Keystore(e.to_string()))?;
+                keystore.decrypt(&passphrase)
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            PayerSource::KeypairFile(path) => format!("keypair file {}", path.display()),
+            PayerSource::Base58Env(var) => format!("environment variable {}", var),
+            PayerSource::Keystore { path, .. } => format!("keystore {}", path.display()),
+        }
+    }
+}
+
+/// Load the payer for `cluster`. Mainnet refuses to start without a configured source;
+/// other clusters fall back to a throwaway keypair so local development still boots.
+pub fn resolve_payer(source: Option<PayerSource>, cluster: &str) -> Result<Keypair, PayerError> {
+    let payer = match source {
+        Some(source) => {
+            let payer = source.load()?;
+            info!("Loaded payer from {}", source.describe());
+            payer
+        }
+        None if cluster.starts_with("mainnet") => return Err(PayerError::Missing),
+        None => {
+            warn!("No payer configured; using an ephemeral keypair on {}", cluster);
+            Keypair::new()
+        }
+    };
+
+    info!("Payer public key: {}", payer.pubkey());
+    Ok(payer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let keypair = Keypair::new();
+        let keystore = Keystore::encrypt(&keypair, "correct horse").unwrap();
+        assert_eq!(keystore.pubkey, keypair.pubkey().to_string());
+
+        let decrypted = keystore.decrypt("correct horse").unwrap();
+        assert_eq!(decrypted.pubkey(), keypair.pubkey());
+        assert!(matches!(keystore.decrypt("battery staple"), Err(PayerError::Decrypt)));
+    }
+
+    #[test]
+    fn test_load_from_keypair_file_and_base58_env() {
+        let keypair = Keypair::new();
+
+        let path = std::env::temp_dir().join(format!("onlypump-payer-{}.json", keypair.pubkey()));
+        let json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+        std::fs::write(&path, json).unwrap();
+        let loaded = PayerSource::KeypairFile(path.clone()).load().unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+        std::fs::remove_file(path).ok();
+
+        let var = format!("ONLYPUMP_TEST_PAYER_{}", keypair.pubkey());
+        std::env::set_var(&var, keypair.to_base58_string());
+        let loaded = PayerSource::Base58Env(var.clone()).load().unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+
+        std::env::set_var(&var, "not-base58-0OIl");
+        assert!(matches!(PayerSource::Base58Env(var.clone()).load(), Err(PayerError::InvalidSecret)));
+        std::env::remove_var(&var);
+    }
+
+    #[test]
+    fn test_mainnet_requires_payer() {
+        assert!(matches!(resolve_payer(None, "mainnet"), Err(PayerError::Missing)));
+        assert!(resolve_payer(None, "devnet").is_ok());
+    }
+}
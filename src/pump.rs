@@ -0,0 +1,682 @@
+//! Pump.fun instructions built without the SDK.
+//!
+//! `PumpFun::create` needs the mint's secret key, which must stay with whoever generated
+//! it. These builders only need its public key: the server signs as fee payer and hands
+//! the transaction back for the mint (and nothing else) to sign.
+//!
+//! The SDK also derives every token account under the legacy SPL Token program, so
+//! Token-2022 launches (`create_v2`) and trades on them are built here as well.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    sysvar,
+    transaction::Transaction,
+};
+use solana_system_interface::program as system_program;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const PUMP_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+pub const MPL_TOKEN_METADATA_ID: Pubkey = solana_sdk::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+/// Fee configuration read by `buy` and `sell`
+pub const FEE_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ");
+/// Accounts `create_v2` passes along even with mayhem mode off
+pub const MAYHEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MAyhSmzXzV1pTf7LsNkrNwkWKTo4ougAJ1PPg47MD4e");
+
+/// Anchor instruction discriminators from the pump program IDL
+const CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+const CREATE_V2_DISCRIMINATOR: [u8; 8] = [214, 144, 76, 236, 95, 139, 49, 180];
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const COLLECT_CREATOR_FEE_DISCRIMINATOR: [u8; 8] = [20, 22, 86, 123, 198, 28, 219, 132];
+
+/// Anchor account discriminators from the pump program IDL
+const GLOBAL_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+/// `create` initializes the mint, metadata and curve; the runtime default of 200k is tight
+pub const CREATE_COMPUTE_UNITS: u32 = 250_000;
+/// A buy or sell, including creating the user's token account
+pub const TRADE_COMPUTE_UNITS: u32 = 150_000;
+
+/// Rent-exempt minimum of a creator vault, which holds no data; a claim leaves it behind
+pub const CREATOR_VAULT_RENT_LAMPORTS: u64 = 890_880;
+
+/// Token program a mint belongs to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MintProgram {
+    /// SPL Token with a Metaplex metadata account, launched with `create`
+    #[default]
+    Spl,
+    /// Token-2022 with the metadata pointer extension, launched with `create_v2`
+    Token2022,
+}
+
+impl MintProgram {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MintProgram::Spl => "spl",
+            MintProgram::Token2022 => "token-2022",
+        }
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            MintProgram::Spl => spl_token::id(),
+            MintProgram::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+
+    /// The program of a mint owned by `owner`, if it is a token program at all
+    pub fn of_owner(owner: &Pubkey) -> Option<Self> {
+        [MintProgram::Spl, MintProgram::Token2022]
+            .into_iter()
+            .find(|program| program.program_id() == *owner)
+    }
+}
+
+impl FromStr for MintProgram {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "spl" | "spl-token" | "legacy" => Ok(MintProgram::Spl),
+            "token-2022" | "token2022" => Ok(MintProgram::Token2022),
+            _ => Err(format!("unknown mint program '{}', expected spl or token-2022", s)),
+        }
+    }
+}
+
+impl fmt::Display for MintProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SubmitError {
+    #[error("transaction is not base64 bincode: {0}")]
+    Malformed(String),
+    #[error("fee payer {0} is not this server's payer")]
+    ForeignPayer(Pubkey),
+    #[error("transaction is missing signatures or has invalid ones")]
+    BadSignatures,
+}
+
+/// Arguments of the pump program's `create` instruction
+pub struct CreateArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Receives the token's creator fees
+    pub creator: Pubkey,
+}
+
+pub fn global_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"global"], &PUMP_PROGRAM_ID).0
+}
+
+pub fn mint_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"mint-authority"], &PUMP_PROGRAM_ID).0
+}
+
+pub fn bonding_curve_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PUMP_PROGRAM_ID).0
+}
+
+pub fn event_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &PUMP_PROGRAM_ID).0
+}
+
+pub fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", MPL_TOKEN_METADATA_ID.as_ref(), mint.as_ref()],
+        &MPL_TOKEN_METADATA_ID,
+    )
+    .0
+}
+
+/// Collects `creator`'s share of trading fees across all of their tokens
+pub fn creator_vault_pda(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], &PUMP_PROGRAM_ID).0
+}
+
+pub fn global_volume_accumulator_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_volume_accumulator"], &PUMP_PROGRAM_ID).0
+}
+
+pub fn user_volume_accumulator_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"user_volume_accumulator", user.as_ref()], &PUMP_PROGRAM_ID).0
+}
+
+pub fn fee_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_config", PUMP_PROGRAM_ID.as_ref()], &FEE_PROGRAM_ID).0
+}
+
+/// `owner`'s associated token account for `mint` under `program`
+pub fn token_account(owner: &Pubkey, mint: &Pubkey, program: MintProgram) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &program.program_id())
+}
+
+/// The pump program's global config, as far as trades need it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Global {
+    pub fee_recipient: Pubkey,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub creator_fee_basis_points: u64,
+}
+
+impl Global {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = AccountReader::new(data, &GLOBAL_DISCRIMINATOR)?;
+        reader.skip(1 + 32)?; // initialized, authority
+        let fee_recipient = reader.pubkey()?;
+        let initial_virtual_token_reserves = reader.u64()?;
+        let initial_virtual_sol_reserves = reader.u64()?;
+        let initial_real_token_reserves = reader.u64()?;
+        let token_total_supply = reader.u64()?;
+        let fee_basis_points = reader.u64()?;
+        reader.skip(32 + 1 + 8)?; // withdraw_authority, enable_migrate, pool_migration_fee
+        let creator_fee_basis_points = reader.u64()?;
+        Some(Self {
+            fee_recipient,
+            initial_virtual_token_reserves,
+            initial_virtual_sol_reserves,
+            initial_real_token_reserves,
+            token_total_supply,
+            fee_basis_points,
+            creator_fee_basis_points,
+        })
+    }
+
+    /// Protocol plus creator fee charged on every trade
+    pub fn total_fee_basis_points(&self) -> u64 {
+        self.fee_basis_points + self.creator_fee_basis_points
+    }
+
+    /// The curve `create` sets up, for quoting a buy in the same transaction
+    pub fn new_curve(&self, creator: Pubkey) -> BondingCurve {
+        BondingCurve {
+            virtual_token_reserves: self.initial_virtual_token_reserves,
+            virtual_sol_reserves: self.initial_virtual_sol_reserves,
+            real_token_reserves: self.initial_real_token_reserves,
+            real_sol_reserves: 0,
+            token_total_supply: self.token_total_supply,
+            complete: false,
+            creator,
+        }
+    }
+}
+
+/// A token's bonding curve account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BondingCurve {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    /// Set once the curve has sold out and the token has moved to an AMM
+    pub complete: bool,
+    pub creator: Pubkey,
+}
+
+impl BondingCurve {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = AccountReader::new(data, &BONDING_CURVE_DISCRIMINATOR)?;
+        Some(Self {
+            virtual_token_reserves: reader.u64()?,
+            virtual_sol_reserves: reader.u64()?,
+            real_token_reserves: reader.u64()?,
+            real_sol_reserves: reader.u64()?,
+            token_total_supply: reader.u64()?,
+            complete: reader.u8()? != 0,
+            creator: reader.pubkey()?,
+        })
+    }
+
+    /// Tokens `lamports` buys once `fee_basis_points` is taken out
+    pub fn buy_quote(&self, lamports: u64, fee_basis_points: u64) -> u64 {
+        let spend = lamports as u128 * 10_000 / (10_000 + fee_basis_points as u128);
+        let virtual_sol = self.virtual_sol_reserves as u128;
+        let virtual_tokens = self.virtual_token_reserves as u128;
+        // Constant product, rounded in the curve's favour
+        let remaining = virtual_sol * virtual_tokens / (virtual_sol + spend) + 1;
+        let tokens = virtual_tokens.saturating_sub(remaining) as u64;
+        tokens.min(self.real_token_reserves)
+    }
+
+    /// Lamports selling `tokens` returns once `fee_basis_points` is taken out
+    pub fn sell_quote(&self, tokens: u64, fee_basis_points: u64) -> u64 {
+        let virtual_sol = self.virtual_sol_reserves as u128;
+        let virtual_tokens = self.virtual_token_reserves as u128;
+        let proceeds = tokens as u128 * virtual_sol / (virtual_tokens + tokens as u128);
+        (proceeds - proceeds * fee_basis_points as u128 / 10_000) as u64
+    }
+}
+
+/// Slippage beyond 100% would let a sell accept nothing at all; handlers refuse it
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+/// Most a buy may cost, or least a sell must return, `slippage_bps` away from `amount`
+pub fn with_slippage(amount: u64, slippage_bps: u16, up: bool) -> u64 {
+    let slippage = amount as u128 * slippage_bps as u128 / 10_000;
+    if up {
+        (amount as u128 + slippage).min(u64::MAX as u128) as u64
+    } else {
+        (amount as u128).saturating_sub(slippage) as u64
+    }
+}
+
+/// Little-endian fields of an Anchor account after its discriminator
+pub(crate) struct AccountReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AccountReader<'a> {
+    pub(crate) fn new(data: &'a [u8], discriminator: &[u8; 8]) -> Option<Self> {
+        let data = data.strip_prefix(discriminator.as_slice())?;
+        Some(Self { data })
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (field, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(field)
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> Option<()> {
+        self.take(len).map(|_| ())
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    pub(crate) fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take(32).map(|b| Pubkey::try_from(b).unwrap())
+    }
+}
+
+// Borsh strings: u32 length, then the bytes
+fn push_string(data: &mut Vec<u8>, field: &str) {
+    data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    data.extend_from_slice(field.as_bytes());
+}
+
+/// `create` for `mint`, paid for by `user`; both must sign
+pub fn create_instruction(mint: &Pubkey, user: &Pubkey, args: &CreateArgs) -> Instruction {
+    let bonding_curve = bonding_curve_pda(mint);
+    let mut data = CREATE_DISCRIMINATOR.to_vec();
+    for field in [&args.name, &args.symbol, &args.uri] {
+        push_string(&mut data, field);
+    }
+    data.extend_from_slice(args.creator.as_ref());
+
+    Instruction {
+        program_id: PUMP_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*mint, true),
+            AccountMeta::new_readonly(mint_authority_pda(), false),
+            AccountMeta::new(bonding_curve, false),
+            AccountMeta::new(token_account(&bonding_curve, mint, MintProgram::Spl), false),
+            AccountMeta::new_readonly(global_pda(), false),
+            AccountMeta::new_readonly(MPL_TOKEN_METADATA_ID, false),
+            AccountMeta::new(metadata_pda(mint), false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(event_authority_pda(), false),
+            AccountMeta::new_readonly(PUMP_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// `create_v2` for `mint`, paid for by `user`: a Token-2022 mint that keeps its metadata
+/// in the mint account itself. Both must sign.
+pub fn create_v2_instruction(mint: &Pubkey, user: &Pubkey, args: &CreateArgs) -> Instruction {
+    let bonding_curve = bonding_curve_pda(mint);
+    let mut data = CREATE_V2_DISCRIMINATOR.to_vec();
+    for field in [&args.name, &args.symbol, &args.uri] {
+        push_string(&mut data, field);
+    }
+    data.extend_from_slice(args.creator.as_ref());
+    data.push(0); // is_mayhem_mode
+
+    let mayhem = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &MAYHEM_PROGRAM_ID).0;
+    let sol_vault = mayhem(&[b"sol-vault"]);
+    Instruction {
+        program_id: PUMP_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*mint, true),
+            AccountMeta::new_readonly(mint_authority_pda(), false),
+            AccountMeta::new(bonding_curve, false),
+            AccountMeta::new(token_account(&bonding_curve, mint, MintProgram::Token2022), false),
+            AccountMeta::new_readonly(global_pda(), false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new(MAYHEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(mayhem(&[b"global-params"]), false),
+            AccountMeta::new(sol_vault, false),
+            AccountMeta::new(mayhem(&[b"mayhem-state", mint.as_ref()]), false),
+            AccountMeta::new(token_account(&sol_vault, mint, MintProgram::Token2022), false),
+            AccountMeta::new_readonly(event_authority_pda(), false),
+            AccountMeta::new_readonly(PUMP_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Accounts a buy or sell on `mint`'s bonding curve touches
+pub struct TradeAccounts {
+    pub mint: Pubkey,
+    pub program: MintProgram,
+    pub user: Pubkey,
+    /// `Global::fee_recipient`
+    pub fee_recipient: Pubkey,
+    /// `BondingCurve::creator`, whose vault receives the creator fee
+    pub creator: Pubkey,
+}
+
+impl TradeAccounts {
+    fn curve_accounts(&self) -> [AccountMeta; 7] {
+        let bonding_curve = bonding_curve_pda(&self.mint);
+        [
+            AccountMeta::new_readonly(global_pda(), false),
+            AccountMeta::new(self.fee_recipient, false),
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(bonding_curve, false),
+            AccountMeta::new(token_account(&bonding_curve, &self.mint, self.program), false),
+            AccountMeta::new(token_account(&self.user, &self.mint, self.program), false),
+            AccountMeta::new(self.user, true),
+        ]
+    }
+}
+
+/// Buy exactly `tokens`, paying at most `max_sol_cost` lamports including fees.
+/// The user's token account must exist; see [`buy_instructions`].
+pub fn buy_instruction(accounts: &TradeAccounts, tokens: u64, max_sol_cost: u64, track_volume: bool) -> Instruction {
+    let mut data = BUY_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&tokens.to_le_bytes());
+    data.extend_from_slice(&max_sol_cost.to_le_bytes());
+    data.push(track_volume as u8);
+
+    let mut metas = accounts.curve_accounts().to_vec();
+    metas.extend([
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(accounts.program.program_id(), false),
+        AccountMeta::new(creator_vault_pda(&accounts.creator), false),
+        AccountMeta::new_readonly(event_authority_pda(), false),
+        AccountMeta::new_readonly(PUMP_PROGRAM_ID, false),
+        AccountMeta::new(global_volume_accumulator_pda(), false),
+        AccountMeta::new(user_volume_accumulator_pda(&accounts.user), false),
+        AccountMeta::new_readonly(fee_config_pda(), false),
+        AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+    ]);
+    Instruction { program_id: PUMP_PROGRAM_ID, accounts: metas, data }
+}
+
+/// Sell `tokens`, receiving at least `min_sol_output` lamports after fees
+pub fn sell_instruction(accounts: &TradeAccounts, tokens: u64, min_sol_output: u64) -> Instruction {
+    let mut data = SELL_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&tokens.to_le_bytes());
+    data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+    // Same accounts as a buy, but the creator vault comes before the token program
+    let mut metas = accounts.curve_accounts().to_vec();
+    metas.extend([
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(creator_vault_pda(&accounts.creator), false),
+        AccountMeta::new_readonly(accounts.program.program_id(), false),
+        AccountMeta::new_readonly(event_authority_pda(), false),
+        AccountMeta::new_readonly(PUMP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(fee_config_pda(), false),
+        AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+    ]);
+    Instruction { program_id: PUMP_PROGRAM_ID, accounts: metas, data }
+}
+
+/// Withdraw everything above rent from `creator`'s vault to `creator`, who must sign
+pub fn collect_creator_fee_instruction(creator: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PUMP_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new(creator_vault_pda(creator), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(event_authority_pda(), false),
+            AccountMeta::new_readonly(PUMP_PROGRAM_ID, false),
+        ],
+        data: COLLECT_CREATOR_FEE_DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// A buy that first creates the user's token account if it does not exist yet
+pub fn buy_instructions(accounts: &TradeAccounts, tokens: u64, max_sol_cost: u64, track_volume: bool) -> Vec<Instruction> {
+    vec![
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &accounts.user,
+            &accounts.user,
+            &accounts.mint,
+            &accounts.program.program_id(),
+        ),
+        buy_instruction(accounts, tokens, max_sol_cost, track_volume),
+    ]
+}
+
+/// `create` or `create_v2`, whichever launches a `program` mint
+pub fn create_instruction_for(program: MintProgram, mint: &Pubkey, user: &Pubkey, args: &CreateArgs) -> Instruction {
+    match program {
+        MintProgram::Spl => create_instruction(mint, user, args),
+        MintProgram::Token2022 => create_v2_instruction(mint, user, args),
+    }
+}
+
+/// Create transaction paid for and signed by `payer`, still waiting for `mint`'s signature
+pub fn prepare_create(
+    payer: &Keypair,
+    mint: &Pubkey,
+    args: &CreateArgs,
+    program: MintProgram,
+    blockhash: Hash,
+) -> Transaction {
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(CREATE_COMPUTE_UNITS),
+        create_instruction_for(program, mint, &payer.pubkey(), args),
+    ];
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.partial_sign(&[payer], blockhash);
+    transaction
+}
+
+/// Decode a transaction a client signed and check it is ready to send on the server's behalf:
+/// the server pays for it (so it was built by [`prepare_create`]) and every signature verifies
+pub fn decode_signed(encoded: &str, payer: &Pubkey) -> Result<Transaction, SubmitError> {
+    let bytes = BASE64.decode(encoded).map_err(|e| SubmitError::Malformed(e.to_string()))?;
+    let transaction: Transaction = bincode::deserialize(&bytes).map_err(|e| SubmitError::Malformed(e.to_string()))?;
+    match transaction.message.account_keys.first() {
+        Some(fee_payer) if fee_payer == payer => {}
+        Some(fee_payer) => return Err(SubmitError::ForeignPayer(*fee_payer)),
+        None => return Err(SubmitError::Malformed("no accounts".to_string())),
+    }
+    // Unsigned slots hold the default signature, which never verifies
+    if transaction.verify().is_err() {
+        return Err(SubmitError::BadSignatures);
+    }
+    Ok(transaction)
+}
+
+pub fn encode(transaction: &Transaction) -> String {
+    BASE64.encode(bincode::serialize(transaction).expect("transactions serialize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(creator: Pubkey) -> CreateArgs {
+        CreateArgs {
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            uri: "https://ipfs.io/ipfs/test".to_string(),
+            creator,
+        }
+    }
+
+    #[test]
+    fn test_create_instruction_layout() {
+        let (mint, user, creator) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = create_instruction(&mint, &user, &args(creator));
+        assert_eq!(ix.program_id, PUMP_PROGRAM_ID);
+        assert_eq!(ix.accounts.len(), 14);
+        let signers: Vec<Pubkey> = ix.accounts.iter().filter(|a| a.is_signer).map(|a| a.pubkey).collect();
+        assert_eq!(signers, vec![mint, user]);
+        assert_eq!(ix.accounts[2].pubkey, bonding_curve_pda(&mint));
+
+        let mut expected = CREATE_DISCRIMINATOR.to_vec();
+        expected.extend_from_slice(&[4, 0, 0, 0]);
+        expected.extend_from_slice(b"Test");
+        assert!(ix.data.starts_with(&expected));
+        assert!(ix.data.ends_with(creator.as_ref()));
+        assert_eq!(ix.data.len(), 8 + (4 + 4) + (4 + 3) + (4 + 25) + 32);
+    }
+
+    #[test]
+    fn test_create_v2_uses_token_2022() {
+        let (mint, user, creator) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = create_instruction_for(MintProgram::Token2022, &mint, &user, &args(creator));
+        assert!(ix.data.starts_with(&CREATE_V2_DISCRIMINATOR));
+        assert_eq!(ix.data.last(), Some(&0)); // no mayhem mode
+        let signers: Vec<Pubkey> = ix.accounts.iter().filter(|a| a.is_signer).map(|a| a.pubkey).collect();
+        assert_eq!(signers, vec![mint, user]);
+
+        // The curve's token account lives under Token-2022, and no Metaplex account is passed
+        let curve_tokens = get_associated_token_address_with_program_id(
+            &bonding_curve_pda(&mint),
+            &mint,
+            &TOKEN_2022_PROGRAM_ID,
+        );
+        assert_eq!(ix.accounts[3].pubkey, curve_tokens);
+        assert!(ix.accounts.iter().any(|a| a.pubkey == TOKEN_2022_PROGRAM_ID));
+        assert!(!ix.accounts.iter().any(|a| a.pubkey == MPL_TOKEN_METADATA_ID || a.pubkey == spl_token::id()));
+    }
+
+    #[test]
+    fn test_mint_program_names_and_owners() {
+        assert_eq!("token-2022".parse::<MintProgram>().unwrap(), MintProgram::Token2022);
+        assert_eq!("SPL".parse::<MintProgram>().unwrap(), MintProgram::Spl);
+        assert!("token-2023".parse::<MintProgram>().is_err());
+        assert_eq!(MintProgram::of_owner(&TOKEN_2022_PROGRAM_ID), Some(MintProgram::Token2022));
+        assert_eq!(MintProgram::of_owner(&spl_token::id()), Some(MintProgram::Spl));
+        assert_eq!(MintProgram::of_owner(&system_program::id()), None);
+
+        // Each program has its own associated token account for the same owner and mint
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_ne!(
+            token_account(&owner, &mint, MintProgram::Spl),
+            token_account(&owner, &mint, MintProgram::Token2022)
+        );
+    }
+
+    #[test]
+    fn test_trades_against_parsed_accounts() {
+        let global = Global::parse(&crate::mock_rpc::global_account_data()).unwrap();
+        assert_eq!(global.total_fee_basis_points(), 105);
+        let mock_curve = crate::mock_rpc::BondingCurve::default();
+        let curve = BondingCurve::parse(&mock_curve.to_account_data()).unwrap();
+        assert_eq!(curve, global.new_curve(mock_curve.creator));
+        assert!(BondingCurve::parse(&mock_curve.to_account_data()[..40]).is_none());
+        assert!(Global::parse(&mock_curve.to_account_data()).is_none());
+
+        // 1 SOL into a fresh curve buys roughly 34.6M tokens, and selling them back loses the fees
+        let tokens = curve.buy_quote(1_000_000_000, global.total_fee_basis_points());
+        assert!((34_000_000_000_000..35_000_000_000_000).contains(&tokens), "{}", tokens);
+        let bought = BondingCurve {
+            virtual_token_reserves: curve.virtual_token_reserves - tokens,
+            virtual_sol_reserves: curve.virtual_sol_reserves + 989_609_104,
+            ..curve.clone()
+        };
+        let proceeds = bought.sell_quote(tokens, global.total_fee_basis_points());
+        assert!((970_000_000..989_609_104).contains(&proceeds), "{}", proceeds);
+        assert_eq!(with_slippage(10_000, 500, true), 10_500);
+        assert_eq!(with_slippage(10_000, 500, false), 9_500);
+        // Past 100% a sell floors at zero instead of underflowing
+        assert_eq!(with_slippage(10_000, MAX_SLIPPAGE_BPS + 1, false), 0);
+
+        let user = Pubkey::new_unique();
+        let accounts = TradeAccounts {
+            mint: Pubkey::new_unique(),
+            program: MintProgram::Token2022,
+            user,
+            fee_recipient: global.fee_recipient,
+            creator: curve.creator,
+        };
+        let buy = buy_instructions(&accounts, tokens, 1_050_000_000, true);
+        assert_eq!(buy.len(), 2);
+        assert_eq!(buy[0].program_id, spl_associated_token_account::id());
+        assert_eq!(buy[1].accounts.len(), 16);
+        assert_eq!(buy[1].accounts[5].pubkey, token_account(&user, &accounts.mint, MintProgram::Token2022));
+        assert_eq!(buy[1].accounts[8].pubkey, TOKEN_2022_PROGRAM_ID);
+        assert_eq!(buy[1].accounts[9].pubkey, creator_vault_pda(&curve.creator));
+        let sell = sell_instruction(&accounts, tokens, 0);
+        assert_eq!(sell.accounts.len(), 14);
+        assert_eq!(sell.accounts[8].pubkey, creator_vault_pda(&curve.creator));
+        assert_eq!(sell.accounts[9].pubkey, TOKEN_2022_PROGRAM_ID);
+        assert!(sell.data.starts_with(&SELL_DISCRIMINATOR));
+    }
+
+    #[test]
+    fn test_prepared_create_needs_the_mint_signature() {
+        let (payer, mint) = (Keypair::new(), Keypair::new());
+        let prepared = prepare_create(
+            &payer,
+            &mint.pubkey(),
+            &args(Pubkey::new_unique()),
+            MintProgram::Spl,
+            Hash::new_unique(),
+        );
+
+        // Only the payer has signed, so the server will not relay it yet
+        let encoded = encode(&prepared);
+        assert!(matches!(decode_signed(&encoded, &payer.pubkey()), Err(SubmitError::BadSignatures)));
+
+        let mut signed = prepared.clone();
+        signed.partial_sign(&[&mint], signed.message.recent_blockhash);
+        let decoded = decode_signed(&encode(&signed), &payer.pubkey()).unwrap();
+        assert_eq!(decoded.signatures, signed.signatures);
+
+        // A client cannot swap in other instructions: the payer's signature covers the message
+        let mut tampered = signed.clone();
+        tampered.message.instructions.pop();
+        assert!(matches!(decode_signed(&encode(&tampered), &payer.pubkey()), Err(SubmitError::BadSignatures)));
+        // Nor relay transactions someone else pays for
+        let foreign = Keypair::new();
+        assert!(matches!(decode_signed(&encode(&signed), &foreign.pubkey()), Err(SubmitError::ForeignPayer(_))));
+        assert!(matches!(decode_signed("not base64!", &payer.pubkey()), Err(SubmitError::Malformed(_))));
+    }
+}
@@ -0,0 +1,402 @@
+//! PumpSwap AMM instructions, for tokens whose bonding curve has completed.
+//!
+//! A graduated token trades against the canonical pool the pump program migrates it
+//! into: the token as base, wrapped SOL as quote. Buys wrap exactly the most SOL the
+//! swap may cost and unwrap whatever is left afterwards, so the payer only ever holds
+//! native SOL between trades.
+
+use crate::pump::{self, AccountReader, MintProgram, FEE_PROGRAM_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token::native_mint;
+
+pub const PUMP_SWAP_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+/// Anchor instruction discriminators from the PumpSwap IDL
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const COLLECT_COIN_CREATOR_FEE_DISCRIMINATOR: [u8; 8] = [160, 57, 89, 42, 181, 139, 43, 66];
+
+/// Anchor account discriminators from the PumpSwap IDL
+const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+const GLOBAL_CONFIG_DISCRIMINATOR: [u8; 8] = [149, 8, 156, 202, 160, 252, 176, 217];
+
+/// Byte offset of `amount` in an SPL Token or Token-2022 token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Owner of migrated pools on the pump program's side
+pub fn pool_authority_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool-authority", mint.as_ref()], &pump::PUMP_PROGRAM_ID).0
+}
+
+/// The pool a completed curve migrates into: index 0, created by the pool authority
+pub fn canonical_pool_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"pool",
+            &0u16.to_le_bytes(),
+            pool_authority_pda(mint).as_ref(),
+            mint.as_ref(),
+            native_mint::id().as_ref(),
+        ],
+        &PUMP_SWAP_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn global_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_config"], &PUMP_SWAP_PROGRAM_ID).0
+}
+
+pub fn event_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &PUMP_SWAP_PROGRAM_ID).0
+}
+
+/// Owns the wrapped SOL account collecting `coin_creator`'s fees on PumpSwap
+pub fn coin_creator_vault_authority_pda(coin_creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator_vault", coin_creator.as_ref()], &PUMP_SWAP_PROGRAM_ID).0
+}
+
+/// Wrapped SOL account holding `coin_creator`'s fees from every pool they are the creator of
+pub fn coin_creator_vault(coin_creator: &Pubkey) -> Pubkey {
+    pump::token_account(&coin_creator_vault_authority_pda(coin_creator), &native_mint::id(), MintProgram::Spl)
+}
+
+pub fn global_volume_accumulator_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_volume_accumulator"], &PUMP_SWAP_PROGRAM_ID).0
+}
+
+pub fn user_volume_accumulator_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"user_volume_accumulator", user.as_ref()], &PUMP_SWAP_PROGRAM_ID).0
+}
+
+pub fn fee_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_config", PUMP_SWAP_PROGRAM_ID.as_ref()], &FEE_PROGRAM_ID).0
+}
+
+/// A PumpSwap pool account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pool {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    /// Default for pools that pay no creator fee
+    pub coin_creator: Pubkey,
+}
+
+impl Pool {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = AccountReader::new(data, &POOL_DISCRIMINATOR)?;
+        reader.skip(1 + 2 + 32)?; // pool_bump, index, creator
+        let base_mint = reader.pubkey()?;
+        let quote_mint = reader.pubkey()?;
+        reader.skip(32)?; // lp_mint
+        let pool_base_token_account = reader.pubkey()?;
+        let pool_quote_token_account = reader.pubkey()?;
+        reader.skip(8)?; // lp_supply
+        // Pools created before creator fees end here
+        let coin_creator = reader.pubkey().unwrap_or_default();
+        Some(Self {
+            base_mint,
+            quote_mint,
+            pool_base_token_account,
+            pool_quote_token_account,
+            coin_creator,
+        })
+    }
+}
+
+/// PumpSwap's fee settings
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobalConfig {
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub protocol_fee_recipient: Pubkey,
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl GlobalConfig {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = AccountReader::new(data, &GLOBAL_CONFIG_DISCRIMINATOR)?;
+        reader.skip(32)?; // admin
+        let lp_fee_basis_points = reader.u64()?;
+        let protocol_fee_basis_points = reader.u64()?;
+        reader.skip(1)?; // disable_flags
+        // Any of the eight recipients will do; the first is always set
+        let protocol_fee_recipient = reader.pubkey()?;
+        reader.skip(7 * 32)?;
+        let coin_creator_fee_basis_points = reader.u64()?;
+        Some(Self {
+            lp_fee_basis_points,
+            protocol_fee_basis_points,
+            protocol_fee_recipient,
+            coin_creator_fee_basis_points,
+        })
+    }
+
+    /// Every fee a swap through `pool` pays, taken on the SOL side
+    pub fn total_fee_basis_points(&self, pool: &Pool) -> u64 {
+        let creator_fee = if pool.coin_creator == Pubkey::default() {
+            0
+        } else {
+            self.coin_creator_fee_basis_points
+        };
+        self.lp_fee_basis_points + self.protocol_fee_basis_points + creator_fee
+    }
+}
+
+/// Token balances of a pool's two vaults
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reserves {
+    pub base: u64,
+    pub quote: u64,
+}
+
+impl Reserves {
+    /// Read both vaults' token account data
+    pub fn parse(base_account: &[u8], quote_account: &[u8]) -> Option<Self> {
+        Some(Self {
+            base: token_amount(base_account)?,
+            quote: token_amount(quote_account)?,
+        })
+    }
+
+    /// Tokens `lamports` buys once `fee_basis_points` is taken out
+    pub fn buy_quote(&self, lamports: u64, fee_basis_points: u64) -> u64 {
+        let spend = lamports as u128 * 10_000 / (10_000 + fee_basis_points as u128);
+        (self.base as u128 * spend / (self.quote as u128 + spend)) as u64
+    }
+
+    /// Lamports selling `tokens` returns once `fee_basis_points` is taken out
+    pub fn sell_quote(&self, tokens: u64, fee_basis_points: u64) -> u64 {
+        let proceeds = self.quote as u128 * tokens as u128 / (self.base as u128 + tokens as u128);
+        (proceeds - proceeds * fee_basis_points as u128 / 10_000) as u64
+    }
+}
+
+/// `amount` of a token account under either token program
+pub fn token_amount(data: &[u8]) -> Option<u64> {
+    let bytes = data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Accounts a swap through a pool touches
+pub struct SwapAccounts {
+    pub pool_address: Pubkey,
+    pub pool: Pool,
+    pub config: GlobalConfig,
+    /// Token program of the pool's base mint
+    pub base_program: MintProgram,
+    pub user: Pubkey,
+}
+
+impl SwapAccounts {
+    fn user_quote_account(&self) -> Pubkey {
+        pump::token_account(&self.user, &self.pool.quote_mint, MintProgram::Spl)
+    }
+
+    fn metas(&self) -> Vec<AccountMeta> {
+        let pool = &self.pool;
+        vec![
+            AccountMeta::new(self.pool_address, false),
+            AccountMeta::new(self.user, true),
+            AccountMeta::new_readonly(global_config_pda(), false),
+            AccountMeta::new_readonly(pool.base_mint, false),
+            AccountMeta::new_readonly(pool.quote_mint, false),
+            AccountMeta::new(pump::token_account(&self.user, &pool.base_mint, self.base_program), false),
+            AccountMeta::new(self.user_quote_account(), false),
+            AccountMeta::new(pool.pool_base_token_account, false),
+            AccountMeta::new(pool.pool_quote_token_account, false),
+            AccountMeta::new_readonly(self.config.protocol_fee_recipient, false),
+            AccountMeta::new(
+                pump::token_account(&self.config.protocol_fee_recipient, &pool.quote_mint, MintProgram::Spl),
+                false,
+            ),
+            AccountMeta::new_readonly(self.base_program.program_id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(event_authority_pda(), false),
+            AccountMeta::new_readonly(PUMP_SWAP_PROGRAM_ID, false),
+            AccountMeta::new(coin_creator_vault(&pool.coin_creator), false),
+            AccountMeta::new_readonly(coin_creator_vault_authority_pda(&pool.coin_creator), false),
+        ]
+    }
+
+    fn create_user_account(&self, mint: &Pubkey, program: MintProgram) -> Instruction {
+        create_associated_token_account_idempotent(&self.user, &self.user, mint, &program.program_id())
+    }
+
+    fn close_quote_account(&self) -> Instruction {
+        close_wrapped_sol(&self.user)
+    }
+}
+
+// Unwrap whatever SOL is left in `owner`'s wrapped SOL account
+fn close_wrapped_sol(owner: &Pubkey) -> Instruction {
+    let account = pump::token_account(owner, &native_mint::id(), MintProgram::Spl);
+    spl_token::instruction::close_account(&spl_token::id(), &account, owner, owner, &[])
+        .expect("close_account takes no multisig signers here")
+}
+
+/// Buy exactly `tokens`, spending at most `max_sol_cost` lamports including fees
+pub fn buy_instruction(accounts: &SwapAccounts, tokens: u64, max_sol_cost: u64, track_volume: bool) -> Instruction {
+    let mut data = BUY_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&tokens.to_le_bytes());
+    data.extend_from_slice(&max_sol_cost.to_le_bytes());
+    data.push(track_volume as u8);
+
+    let mut metas = accounts.metas();
+    metas.extend([
+        AccountMeta::new(global_volume_accumulator_pda(), false),
+        AccountMeta::new(user_volume_accumulator_pda(&accounts.user), false),
+        AccountMeta::new_readonly(fee_config_pda(), false),
+        AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+    ]);
+    Instruction { program_id: PUMP_SWAP_PROGRAM_ID, accounts: metas, data }
+}
+
+/// Sell `tokens`, receiving at least `min_sol_output` lamports after fees
+pub fn sell_instruction(accounts: &SwapAccounts, tokens: u64, min_sol_output: u64) -> Instruction {
+    let mut data = SELL_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&tokens.to_le_bytes());
+    data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+    let mut metas = accounts.metas();
+    metas.extend([
+        AccountMeta::new_readonly(fee_config_pda(), false),
+        AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+    ]);
+    Instruction { program_id: PUMP_SWAP_PROGRAM_ID, accounts: metas, data }
+}
+
+/// A buy wrapped in its token account setup: wrap `max_sol_cost`, swap, unwrap the rest
+pub fn buy_instructions(accounts: &SwapAccounts, tokens: u64, max_sol_cost: u64, track_volume: bool) -> Vec<Instruction> {
+    let quote_account = accounts.user_quote_account();
+    vec![
+        accounts.create_user_account(&accounts.pool.base_mint, accounts.base_program),
+        accounts.create_user_account(&accounts.pool.quote_mint, MintProgram::Spl),
+        system_instruction::transfer(&accounts.user, &quote_account, max_sol_cost),
+        spl_token::instruction::sync_native(&spl_token::id(), &quote_account).expect("wrapped SOL account"),
+        buy_instruction(accounts, tokens, max_sol_cost, track_volume),
+        accounts.close_quote_account(),
+    ]
+}
+
+/// A sell that receives wrapped SOL and unwraps it
+pub fn sell_instructions(accounts: &SwapAccounts, tokens: u64, min_sol_output: u64) -> Vec<Instruction> {
+    vec![
+        accounts.create_user_account(&accounts.pool.quote_mint, MintProgram::Spl),
+        sell_instruction(accounts, tokens, min_sol_output),
+        accounts.close_quote_account(),
+    ]
+}
+
+/// Move `coin_creator`'s PumpSwap fees to their wallet as native SOL; they must sign
+pub fn collect_coin_creator_fee_instructions(coin_creator: &Pubkey) -> Vec<Instruction> {
+    let wrapped_sol = pump::token_account(coin_creator, &native_mint::id(), MintProgram::Spl);
+    let collect = Instruction {
+        program_id: PUMP_SWAP_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(native_mint::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*coin_creator, true),
+            AccountMeta::new_readonly(coin_creator_vault_authority_pda(coin_creator), false),
+            AccountMeta::new(coin_creator_vault(coin_creator), false),
+            AccountMeta::new(wrapped_sol, false),
+            AccountMeta::new_readonly(event_authority_pda(), false),
+            AccountMeta::new_readonly(PUMP_SWAP_PROGRAM_ID, false),
+        ],
+        data: COLLECT_COIN_CREATOR_FEE_DISCRIMINATOR.to_vec(),
+    };
+    vec![
+        create_associated_token_account_idempotent(coin_creator, coin_creator, &native_mint::id(), &spl_token::id()),
+        collect,
+        close_wrapped_sol(coin_creator),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc;
+
+    fn accounts(pool: Pool, config: GlobalConfig, base_program: MintProgram) -> SwapAccounts {
+        SwapAccounts {
+            pool_address: Pubkey::new_unique(),
+            pool,
+            config,
+            base_program,
+            user: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_pool_accounts_and_quotes() {
+        let mint = Pubkey::new_unique();
+        let coin_creator = Pubkey::new_unique();
+        let mock_pool = mock_rpc::SwapPool::new(&mint, spl_token::id(), coin_creator);
+        let pool = Pool::parse(&mock_pool.to_account_data()).unwrap();
+        assert_eq!((pool.base_mint, pool.quote_mint), (mint, native_mint::id()));
+        assert_eq!(pool.coin_creator, coin_creator);
+        // Pools from before creator fees are shorter
+        let legacy = Pool::parse(&mock_pool.to_account_data()[..211]).unwrap();
+        assert_eq!(legacy.coin_creator, Pubkey::default());
+
+        let config = GlobalConfig::parse(&mock_rpc::swap_global_config_data()).unwrap();
+        assert_eq!(config.total_fee_basis_points(&pool), 30);
+        assert_eq!(config.total_fee_basis_points(&legacy), 25);
+
+        let reserves = Reserves::parse(&mock_pool.base_vault_data(), &mock_pool.quote_vault_data()).unwrap();
+        assert_eq!(reserves, Reserves { base: mock_pool.base_reserve, quote: mock_pool.quote_reserve });
+        // 1 SOL of an 85 SOL pool buys a little over 1/86 of the base reserve, less fees
+        let tokens = reserves.buy_quote(1_000_000_000, 30);
+        let without_fees = reserves.base / 86;
+        assert!(tokens < without_fees && tokens > without_fees * 99 / 100, "{}", tokens);
+        // Selling them straight back returns what went into the pool, less fees again
+        let after = Reserves { base: reserves.base - tokens, quote: reserves.quote + 997_008_973 };
+        let proceeds = after.sell_quote(tokens, 30);
+        assert!((990_000_000..997_008_973).contains(&proceeds), "{}", proceeds);
+        assert_eq!(canonical_pool_pda(&mint), mock_rpc::swap_pool_address(&mint));
+    }
+
+    #[test]
+    fn test_swaps_wrap_and_unwrap_sol() {
+        let mint = Pubkey::new_unique();
+        let mock_pool = mock_rpc::SwapPool::new(&mint, pump::TOKEN_2022_PROGRAM_ID, Pubkey::new_unique());
+        let pool = Pool::parse(&mock_pool.to_account_data()).unwrap();
+        let config = GlobalConfig::parse(&mock_rpc::swap_global_config_data()).unwrap();
+        let accounts = accounts(pool, config, MintProgram::Token2022);
+        let user_tokens = pump::token_account(&accounts.user, &mint, MintProgram::Token2022);
+
+        let buy = buy_instructions(&accounts, 1_000, 2_000, false);
+        let programs: Vec<Pubkey> = buy.iter().map(|ix| ix.program_id).collect();
+        assert_eq!(
+            programs,
+            vec![
+                spl_associated_token_account::id(),
+                spl_associated_token_account::id(),
+                system_program::id(),
+                spl_token::id(),
+                PUMP_SWAP_PROGRAM_ID,
+                spl_token::id(),
+            ]
+        );
+        let swap = &buy[4];
+        assert_eq!(swap.accounts.len(), 23);
+        assert_eq!(swap.accounts[5].pubkey, user_tokens);
+        assert_eq!(swap.accounts[11].pubkey, pump::TOKEN_2022_PROGRAM_ID);
+        assert_eq!(swap.accounts[12].pubkey, spl_token::id());
+        assert_eq!(swap.data[8..24], [1_000u64.to_le_bytes(), 2_000u64.to_le_bytes()].concat());
+
+        let sell = sell_instructions(&accounts, 1_000, 900);
+        assert_eq!(sell.len(), 3);
+        assert_eq!(sell[1].accounts.len(), 21);
+        assert!(sell[1].data.starts_with(&SELL_DISCRIMINATOR));
+    }
+}
@@ -0,0 +1,368 @@
+//! Pump trades built from [`crate::pump`] and [`crate::pump_swap`] instructions and sent
+//! through the RPC pool.
+//!
+//! `PumpFun::buy`, `sell` and `create` only know SPL Token mints on their bonding curve.
+//! Token-2022 mints, whether launched here with `create_v2` or elsewhere, are traded
+//! through these instead, and so is every token that has graduated to PumpSwap.
+
+use crate::pump::{self, BondingCurve, CreateArgs, Global, MintProgram, TradeAccounts};
+use crate::pump_swap::{self, GlobalConfig, Pool, Reserves, SwapAccounts};
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Slippage when the request does not set one, as in the SDK
+pub const DEFAULT_SLIPPAGE_BPS: u16 = 500;
+
+#[derive(Debug, Error)]
+pub enum TradeError {
+    #[error("mint {0} not found")]
+    MintNotFound(Pubkey),
+    #[error("{mint} is owned by {owner}, not a token program")]
+    NotAMint { mint: Pubkey, owner: Pubkey },
+    #[error("bonding curve account not found for {0}")]
+    CurveNotFound(Pubkey),
+    #[error("bonding curve for {0} is complete")]
+    CurveComplete(Pubkey),
+    #[error("no PumpSwap pool for {0} yet")]
+    PoolNotFound(Pubkey),
+    #[error("malformed {0} account")]
+    Malformed(&'static str),
+    #[error("no tokens to sell")]
+    NothingToSell,
+    #[error("rpc error: {0}")]
+    Rpc(#[from] ClientError),
+}
+
+impl TradeError {
+    /// Whether the transaction may have landed despite the error, e.g. when confirmation timed out.
+    /// Only errors before sending and transactions the cluster rejected are known not to have.
+    pub fn may_have_landed(&self) -> bool {
+        match self {
+            TradeError::Rpc(e) => !matches!(
+                e.get_transaction_error(),
+                Some(error) if error != TransactionError::AlreadyProcessed
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// SOL going into a buy
+#[derive(Clone, Copy, Debug)]
+pub struct Purchase {
+    /// Spent in total, fees included
+    pub lamports: u64,
+    pub slippage_bps: Option<u16>,
+    pub track_volume: bool,
+}
+
+impl Purchase {
+    fn quote(&self, curve: &BondingCurve, global: &Global) -> (u64, u64) {
+        let tokens = curve.buy_quote(self.lamports, global.total_fee_basis_points());
+        let max_sol_cost = pump::with_slippage(self.lamports, self.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS), true);
+        (tokens, max_sol_cost)
+    }
+}
+
+/// Where a token trades
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Venue {
+    BondingCurve,
+    /// The curve has completed and the token migrated to its PumpSwap pool
+    PumpSwap,
+}
+
+impl Venue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Venue::BondingCurve => "bonding_curve",
+            Venue::PumpSwap => "pump_swap",
+        }
+    }
+}
+
+/// How to trade a mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub program: MintProgram,
+    pub venue: Venue,
+}
+
+/// Token program of `mint` (from the owner of its account) and whether its curve has graduated
+pub async fn route(rpc: &RpcClient, mint: &Pubkey) -> Result<Route, TradeError> {
+    let keys = [*mint, pump::bonding_curve_pda(mint), pump_swap::canonical_pool_pda(mint)];
+    let [mint_account, curve_account, pool_account]: [_; 3] = rpc
+        .get_multiple_accounts(&keys)
+        .await?
+        .try_into()
+        .map_err(|_| TradeError::Malformed("getMultipleAccounts"))?;
+
+    let mint_account = mint_account.ok_or(TradeError::MintNotFound(*mint))?;
+    let program = MintProgram::of_owner(&mint_account.owner).ok_or(TradeError::NotAMint {
+        mint: *mint,
+        owner: mint_account.owner,
+    })?;
+    let curve_account = curve_account.ok_or(TradeError::CurveNotFound(*mint))?;
+    let curve = BondingCurve::parse(&curve_account.data).ok_or(TradeError::Malformed("bonding curve"))?;
+    if !curve.complete {
+        return Ok(Route { program, venue: Venue::BondingCurve });
+    }
+    // A completed curve is migrated by the pump program shortly after; until then there is no pool
+    if pool_account.is_none() {
+        return Err(TradeError::PoolNotFound(*mint));
+    }
+    Ok(Route { program, venue: Venue::PumpSwap })
+}
+
+pub async fn global(rpc: &RpcClient) -> Result<Global, TradeError> {
+    let data = rpc.get_account_data(&pump::global_pda()).await?;
+    Global::parse(&data).ok_or(TradeError::Malformed("global"))
+}
+
+pub async fn bonding_curve(rpc: &RpcClient, mint: &Pubkey) -> Result<BondingCurve, TradeError> {
+    let account = rpc
+        .get_account_with_commitment(&pump::bonding_curve_pda(mint), rpc.commitment())
+        .await?
+        .value
+        .ok_or(TradeError::CurveNotFound(*mint))?;
+    BondingCurve::parse(&account.data).ok_or(TradeError::Malformed("bonding curve"))
+}
+
+pub async fn buy(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+    purchase: Purchase,
+) -> Result<Signature, TradeError> {
+    let global = global(rpc).await?;
+    let curve = bonding_curve(rpc, mint).await?;
+    if curve.complete {
+        return Err(TradeError::CurveComplete(*mint));
+    }
+
+    let (tokens, max_sol_cost) = purchase.quote(&curve, &global);
+    let accounts = trade_accounts(mint, program, payer, &global, &curve);
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(pump::TRADE_COMPUTE_UNITS)];
+    instructions.extend(pump::buy_instructions(&accounts, tokens, max_sol_cost, purchase.track_volume));
+    send(rpc, payer, &instructions, None).await
+}
+
+/// Sell `tokens` of `mint`, or the payer's whole balance if `None`
+pub async fn sell(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+    tokens: Option<u64>,
+    slippage_bps: Option<u16>,
+) -> Result<Signature, TradeError> {
+    let global = global(rpc).await?;
+    let curve = bonding_curve(rpc, mint).await?;
+    if curve.complete {
+        return Err(TradeError::CurveComplete(*mint));
+    }
+
+    let tokens = tokens_to_sell(rpc, payer, mint, program, tokens).await?;
+    let proceeds = curve.sell_quote(tokens, global.total_fee_basis_points());
+    let min_sol_output = pump::with_slippage(proceeds, slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS), false);
+    let accounts = trade_accounts(mint, program, payer, &global, &curve);
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(pump::TRADE_COMPUTE_UNITS),
+        pump::sell_instruction(&accounts, tokens, min_sol_output),
+    ];
+    send(rpc, payer, &instructions, None).await
+}
+
+/// Launch `mint` under `program`, paid for by `payer`
+pub async fn create(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Keypair,
+    args: &CreateArgs,
+    program: MintProgram,
+) -> Result<Signature, TradeError> {
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(pump::CREATE_COMPUTE_UNITS),
+        pump::create_instruction_for(program, &mint.pubkey(), &payer.pubkey(), args),
+    ];
+    send(rpc, payer, &instructions, Some(mint)).await
+}
+
+/// Launch `mint` and buy into it in the same transaction
+pub async fn create_and_buy(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Keypair,
+    args: &CreateArgs,
+    program: MintProgram,
+    purchase: Purchase,
+) -> Result<Signature, TradeError> {
+    // The curve does not exist yet; quote against the one `create` will set up
+    let global = global(rpc).await?;
+    let curve = global.new_curve(args.creator);
+    let (tokens, max_sol_cost) = purchase.quote(&curve, &global);
+    let accounts = trade_accounts(&mint.pubkey(), program, payer, &global, &curve);
+
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(pump::CREATE_COMPUTE_UNITS + pump::TRADE_COMPUTE_UNITS),
+        pump::create_instruction_for(program, &mint.pubkey(), &payer.pubkey(), args),
+    ];
+    instructions.extend(pump::buy_instructions(&accounts, tokens, max_sol_cost, purchase.track_volume));
+    send(rpc, payer, &instructions, Some(mint)).await
+}
+
+/// Buy `mint` from its PumpSwap pool
+pub async fn swap_buy(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+    purchase: Purchase,
+) -> Result<Signature, TradeError> {
+    let (accounts, reserves) = swap_pool(rpc, payer, mint, program).await?;
+    let fee_basis_points = accounts.config.total_fee_basis_points(&accounts.pool);
+    let tokens = reserves.buy_quote(purchase.lamports, fee_basis_points);
+    let max_sol_cost = pump::with_slippage(
+        purchase.lamports,
+        purchase.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS),
+        true,
+    );
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(pump::TRADE_COMPUTE_UNITS)];
+    instructions.extend(pump_swap::buy_instructions(&accounts, tokens, max_sol_cost, purchase.track_volume));
+    send(rpc, payer, &instructions, None).await
+}
+
+/// Sell `tokens` of `mint` into its PumpSwap pool, or the payer's whole balance if `None`
+pub async fn swap_sell(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+    tokens: Option<u64>,
+    slippage_bps: Option<u16>,
+) -> Result<Signature, TradeError> {
+    let (accounts, reserves) = swap_pool(rpc, payer, mint, program).await?;
+    let tokens = tokens_to_sell(rpc, payer, mint, program, tokens).await?;
+    let proceeds = reserves.sell_quote(tokens, accounts.config.total_fee_basis_points(&accounts.pool));
+    let min_sol_output = pump::with_slippage(proceeds, slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS), false);
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(pump::TRADE_COMPUTE_UNITS)];
+    instructions.extend(pump_swap::sell_instructions(&accounts, tokens, min_sol_output));
+    send(rpc, payer, &instructions, None).await
+}
+
+/// The canonical pool for `mint`, its fee config and current reserves
+async fn swap_pool(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+) -> Result<(SwapAccounts, Reserves), TradeError> {
+    let pool_address = pump_swap::canonical_pool_pda(mint);
+    let accounts = rpc.get_multiple_accounts(&[pool_address, pump_swap::global_config_pda()]).await?;
+    let (pool, config) = (&accounts[0], &accounts[1]);
+    let pool = pool.as_ref().ok_or(TradeError::PoolNotFound(*mint))?;
+    let pool = Pool::parse(&pool.data).ok_or(TradeError::Malformed("PumpSwap pool"))?;
+    let config = config.as_ref().ok_or(TradeError::Malformed("PumpSwap global config"))?;
+    let config = GlobalConfig::parse(&config.data).ok_or(TradeError::Malformed("PumpSwap global config"))?;
+
+    let vaults = rpc
+        .get_multiple_accounts(&[pool.pool_base_token_account, pool.pool_quote_token_account])
+        .await?;
+    let reserves = match (&vaults[0], &vaults[1]) {
+        (Some(base), Some(quote)) => Reserves::parse(&base.data, &quote.data),
+        _ => None,
+    }
+    .ok_or(TradeError::Malformed("PumpSwap pool vault"))?;
+
+    let accounts = SwapAccounts {
+        pool_address,
+        pool,
+        config,
+        base_program: program,
+        user: payer.pubkey(),
+    };
+    Ok((accounts, reserves))
+}
+
+// The requested amount, or the payer's whole balance of `mint`
+async fn tokens_to_sell(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    program: MintProgram,
+    tokens: Option<u64>,
+) -> Result<u64, TradeError> {
+    let tokens = match tokens {
+        Some(tokens) => tokens,
+        None => {
+            let balance = rpc
+                .get_token_account_balance(&pump::token_account(&payer.pubkey(), mint, program))
+                .await?;
+            balance.amount.parse().map_err(|_| TradeError::Malformed("token"))?
+        }
+    };
+    if tokens == 0 {
+        return Err(TradeError::NothingToSell);
+    }
+    Ok(tokens)
+}
+
+fn trade_accounts(
+    mint: &Pubkey,
+    program: MintProgram,
+    payer: &Keypair,
+    global: &Global,
+    curve: &BondingCurve,
+) -> TradeAccounts {
+    TradeAccounts {
+        mint: *mint,
+        program,
+        user: payer.pubkey(),
+        fee_recipient: global.fee_recipient,
+        creator: curve.creator,
+    }
+}
+
+async fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    mint: Option<&Keypair>,
+) -> Result<Signature, TradeError> {
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut signers = vec![payer];
+    signers.extend(mint);
+    transaction.sign(&signers, blockhash);
+    Ok(rpc.send_and_confirm_transaction(&transaction).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::client_error::ClientErrorKind;
+    use solana_sdk::instruction::InstructionError;
+
+    #[test]
+    fn test_only_rejected_sends_are_known_not_to_have_landed() {
+        let rejected = ClientErrorKind::TransactionError(TransactionError::InstructionError(1, InstructionError::Custom(0)));
+        assert!(!TradeError::Rpc(rejected.into()).may_have_landed());
+        assert!(!TradeError::CurveComplete(Pubkey::new_unique()).may_have_landed());
+
+        let unconfirmed = ClientErrorKind::Custom("unable to confirm transaction".to_string());
+        assert!(TradeError::Rpc(unconfirmed.into()).may_have_landed());
+        let duplicate = ClientErrorKind::TransactionError(TransactionError::AlreadyProcessed);
+        assert!(TradeError::Rpc(duplicate.into()).may_have_landed());
+    }
+}
@@ -0,0 +1,419 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Request bodies are buffered to find the wallet; matches axum's default `Json` limit
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Buckets idle this long are full again and can be dropped
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Endpoints grouped by how expensive they are for us
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RouteClass {
+    /// Lookups: curve state, stats, job status
+    Read,
+    /// Buys, sells and other state-changing calls
+    Trade,
+    /// Token creation, vanity jobs and reservations, which spend pool keys or CPU
+    Create,
+}
+
+impl RouteClass {
+    /// `None` for routes that are never limited (health probes, CORS preflight, and
+    /// vanity workers, which authenticate every batch and may share one egress IP)
+    pub fn of(method: &Method, path: &str) -> Option<Self> {
+        if method == Method::OPTIONS || path == "/health" || path.starts_with("/health/") || path == "/vanity/ingest" {
+            return None;
+        }
+        if method == Method::GET || method == Method::HEAD {
+            return Some(RouteClass::Read);
+        }
+        match path {
+            "/tx/create" | "/tx/create-and-buy" | "/tx/prepare-create" | "/vanity/jobs" | "/vanity/reserve" => Some(RouteClass::Create),
+            _ => Some(RouteClass::Trade),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Read => "read",
+            RouteClass::Trade => "trade",
+            RouteClass::Create => "create",
+        }
+    }
+}
+
+/// Token bucket size and refill rate, written `per_minute[:burst]` (e.g. `30:10`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl Budget {
+    pub const fn new(per_minute: u32, burst: u32) -> Self {
+        Self { per_minute, burst }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+}
+
+impl FromStr for Budget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |v: &str| v.trim().parse::<u32>().ok().filter(|n| *n > 0);
+        let budget = match s.split_once(':') {
+            Some((per_minute, burst)) => parse(per_minute).zip(parse(burst)).map(|(p, b)| Budget::new(p, b)),
+            None => parse(s).map(|p| Budget::new(p, p)),
+        };
+        budget.ok_or_else(|| format!("invalid rate limit '{}', expected per_minute[:burst]", s))
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/min, burst {}", self.per_minute, self.burst)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub read: Budget,
+    pub trade: Budget,
+    pub create: Budget,
+    /// Take the client IP from `X-Forwarded-For`; only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            read: Budget::new(120, 60),
+            trade: Budget::new(30, 10),
+            create: Budget::new(6, 3),
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn budget(&self, class: RouteClass) -> Budget {
+        match class {
+            RouteClass::Read => self.read,
+            RouteClass::Trade => self.trade,
+            RouteClass::Create => self.create,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Ip(IpAddr),
+    Wallet(String),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Ip(ip) => write!(f, "ip {}", ip),
+            Key::Wallet(wallet) => write!(f, "wallet {}", wallet),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets per (route class, client IP) and per (route class, signed-in wallet)
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<(RouteClass, Key), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take one token, or say how long until one is available
+    fn check(&self, class: RouteClass, key: &Key, now: Instant) -> Result<(), Duration> {
+        let budget = self.config.budget(class);
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry((class, key.clone())).or_insert(Bucket {
+            tokens: budget.burst as f64,
+            updated: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * budget.refill_per_sec()).min(budget.burst as f64);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / budget.refill_per_sec()))
+        }
+    }
+
+    /// Forget buckets that have refilled completely; they would be recreated identical
+    fn prune(&self, now: Instant) {
+        let config = &self.config;
+        self.buckets.lock().unwrap().retain(|(class, _), bucket| {
+            let budget = config.budget(*class);
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens + elapsed * budget.refill_per_sec() < budget.burst as f64
+        });
+    }
+
+    pub fn spawn_pruner(self: &Arc<Self>) {
+        let limiter = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                limiter.prune(Instant::now());
+            }
+        });
+    }
+}
+
+#[derive(Deserialize)]
+struct WalletClaim {
+    wallet_address: String,
+    signature: String,
+    message: String,
+}
+
+/// Axum middleware enforcing [`RateLimiter`] budgets
+pub async fn rate_limit(State(limiter): State<Arc<RateLimiter>>, request: Request, next: Next) -> Response {
+    let class = match RouteClass::of(request.method(), request.uri().path()) {
+        Some(class) if limiter.config.enabled => class,
+        _ => return next.run(request).await,
+    };
+
+    let ip = client_ip(&request, limiter.config.trust_forwarded_for);
+    let now = Instant::now();
+    if let Err(retry_after) = limiter.check(class, &Key::Ip(ip), now) {
+        return too_many_requests(class, &Key::Ip(ip), retry_after);
+    }
+
+    if request.method() == Method::GET || request.method() == Method::HEAD {
+        return next.run(request).await;
+    }
+
+    // The wallet is only trusted once its signature checks out, so nobody can burn
+    // another wallet's budget by putting its address in a request
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    if let Ok(claim) = serde_json::from_slice::<WalletClaim>(&bytes) {
+        if crate::auth::verify_wallet_signature(&claim.wallet_address, &claim.signature, &claim.message).is_ok() {
+            let key = Key::Wallet(claim.wallet_address);
+            if let Err(retry_after) = limiter.check(class, &key, now) {
+                return too_many_requests(class, &key, retry_after);
+            }
+        }
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+fn client_ip(request: &Request, trust_forwarded_for: bool) -> IpAddr {
+    let forwarded = if trust_forwarded_for {
+        forwarded_for(request.headers())
+    } else {
+        None
+    };
+    let ip = forwarded
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        })
+        .unwrap_or(IpAddr::from([0, 0, 0, 0]));
+
+    // A single IPv6 client usually controls a whole /64
+    match ip {
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], segments[2], segments[3], 0, 0, 0, 0))
+        }
+        v4 => v4,
+    }
+}
+
+/// Left-most `X-Forwarded-For` entry: the client as seen by the first proxy
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")?
+        .to_str()
+        .ok()?
+        .split(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn too_many_requests(class: RouteClass, key: &Key, retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    warn!("Rate limited {} on {} routes; retry in {}s", key, class.as_str(), secs);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+        Json(serde_json::json!({
+            "error": "rate limit exceeded",
+            "retry_after": secs,
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use solana_sdk::{signature::Keypair, signer::Signer};
+    use tower::ServiceExt;
+
+    fn limiter(trade: Budget) -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            trade,
+            ..RateLimitConfig::default()
+        }))
+    }
+
+    fn app(limiter: Arc<RateLimiter>) -> Router {
+        Router::new()
+            .route("/tx/buy", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(limiter, rate_limit))
+    }
+
+    fn buy(ip: [u8; 4], body: String) -> Request {
+        let mut request = Request::post("/tx/buy")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 40_000))));
+        request
+    }
+
+    fn signed_body(wallet: &Keypair) -> String {
+        let message = "Connect to OnlyPump - Nonce: 1";
+        serde_json::json!({
+            "wallet_address": wallet.pubkey().to_string(),
+            "signature": wallet.sign_message(message.as_bytes()).to_string(),
+            "message": message,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = limiter(Budget::new(60, 2));
+        let key = Key::Ip(IpAddr::from([10, 0, 0, 1]));
+        let start = Instant::now();
+
+        assert!(limiter.check(RouteClass::Trade, &key, start).is_ok());
+        assert!(limiter.check(RouteClass::Trade, &key, start).is_ok());
+        let retry_after = limiter.check(RouteClass::Trade, &key, start).unwrap_err();
+        assert!(retry_after <= Duration::from_secs(1));
+
+        // One token per second at 60/min; other classes have their own budget
+        assert!(limiter.check(RouteClass::Trade, &key, start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.check(RouteClass::Read, &key, start).is_ok());
+
+        limiter.prune(start + Duration::from_secs(10));
+        assert!(limiter.buckets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_route_classes_and_budget_parsing() {
+        assert_eq!(RouteClass::of(&Method::POST, "/tx/create"), Some(RouteClass::Create));
+        assert_eq!(RouteClass::of(&Method::POST, "/tx/sell"), Some(RouteClass::Trade));
+        assert_eq!(RouteClass::of(&Method::POST, "/tx/prepare-create"), Some(RouteClass::Create));
+        assert_eq!(RouteClass::of(&Method::POST, "/tx/submit"), Some(RouteClass::Trade));
+        assert_eq!(RouteClass::of(&Method::GET, "/token/abc/curve"), Some(RouteClass::Read));
+        assert_eq!(RouteClass::of(&Method::GET, "/creator/abc/fees"), Some(RouteClass::Read));
+        assert_eq!(RouteClass::of(&Method::POST, "/creator/fees/claim"), Some(RouteClass::Trade));
+        assert_eq!(RouteClass::of(&Method::GET, "/health"), None);
+        assert_eq!(RouteClass::of(&Method::OPTIONS, "/tx/buy"), None);
+        assert_eq!(RouteClass::of(&Method::POST, "/vanity/ingest"), None);
+        assert_eq!(RouteClass::of(&Method::POST, "/vanity/reserve"), Some(RouteClass::Create));
+
+        assert_eq!("30:10".parse::<Budget>().unwrap(), Budget::new(30, 10));
+        assert_eq!("30".parse::<Budget>().unwrap(), Budget::new(30, 30));
+        assert!("0:5".parse::<Budget>().is_err());
+        assert!("fast".parse::<Budget>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_limits_by_ip_with_retry_after() {
+        let app = app(limiter(Budget::new(1, 2)));
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(buy([10, 0, 0, 1], "{}".to_string())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = app.clone().oneshot(buy([10, 0, 0, 1], "{}".to_string())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        // A different client is unaffected
+        let response = app.oneshot(buy([10, 0, 0, 2], "{}".to_string())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_limits_signed_wallet_across_ips() {
+        let app = app(limiter(Budget::new(1, 2)));
+        let wallet = Keypair::new();
+
+        for ip in [[10, 0, 0, 1], [10, 0, 0, 2]] {
+            let response = app.clone().oneshot(buy(ip, signed_body(&wallet))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = app.clone().oneshot(buy([10, 0, 0, 3], signed_body(&wallet))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // An unsigned claim to the same wallet does not count against it
+        let forged = serde_json::json!({
+            "wallet_address": wallet.pubkey().to_string(),
+            "signature": "1111111111111111111111111111111111111111111111111111111111111111",
+            "message": "x",
+        });
+        let response = app.oneshot(buy([10, 0, 0, 4], forged.to_string())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
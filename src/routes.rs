@@ -0,0 +1,302 @@
+use crate::health::{self, Check, CheckStatus};
+use crate::metrics::{self, metrics};
+use crate::rate_limit::{self, RateLimiter};
+use crate::rpc_pool::EndpointStats;
+use crate::vanity_ingest::{IngestEnvelope, IngestError, IngestOutcome};
+use crate::vanity_jobs::{JobError, JobView};
+use crate::vanity_reservations::{ReservationError, ReservationView};
+use crate::{auth, creator, curve, logging, trading, AppState};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Json},
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::{info, warn, Level};
+
+#[derive(Serialize)]
+struct ReadinessResponse {
+    status: CheckStatus,
+    cluster: String,
+    payer: String,
+    checks: Vec<Check>,
+    rpc_endpoints: Vec<EndpointStats>,
+}
+
+#[derive(Deserialize)]
+struct CreateVanityJobRequest {
+    pattern: String,
+    // Wallet integration fields
+    wallet_address: String,
+    signature: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ReserveVanityRequest {
+    // Named vanity pool to draw from (defaults to the service's default pool)
+    pool: Option<String>,
+    // Wallet integration fields
+    wallet_address: String,
+    signature: String,
+    message: String,
+}
+
+/// All routes with rate limiting, metrics, tracing, request IDs and CORS applied
+pub fn build_router(state: AppState) -> Router {
+    // Throttle by client IP and signed-in wallet before any handler runs
+    let rate_limiter = Arc::new(RateLimiter::new(state.config.rate_limit.clone()));
+    rate_limiter.spawn_pruner();
+    let cors = state.config.cors.layer();
+    
+    Router::new()
+        .route("/health", get(readiness_handler))
+        .route("/health/live", get(liveness_handler))
+        .route("/health/ready", get(readiness_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/wallet/connect", get(auth::wallet_connect_handler))
+        .route("/tx/create", post(trading::create_token_handler))
+        .route("/tx/create-and-buy", post(trading::create_and_buy_handler))
+        .route("/tx/prepare-create", post(trading::prepare_create_handler))
+        .route("/tx/submit", post(trading::submit_transaction_handler))
+        .route("/tx/buy", post(trading::buy_token_handler))
+        .route("/tx/sell", post(trading::sell_token_handler))
+        .route("/token/:mint/curve", get(curve::get_curve_handler))
+        .route("/creator/:wallet/fees", get(creator::get_creator_fees_handler))
+        .route("/creator/fees/claim", post(creator::claim_creator_fees_handler))
+        .route("/vanity/stats", get(vanity_stats_handler))
+        .route("/vanity/ingest", post(vanity_ingest_handler))
+        .route("/vanity/reserve", post(reserve_vanity_handler))
+        .route("/vanity/jobs", post(create_vanity_job_handler))
+        .route("/vanity/jobs/:id", get(get_vanity_job_handler).delete(cancel_vanity_job_handler))
+        .layer(middleware::from_fn_with_state(rate_limiter, rate_limit::rate_limit))
+        .layer(middleware::from_fn(metrics::track_requests))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(logging::request_span)
+                .on_response(DefaultOnResponse::new().level(Level::INFO).latency_unit(LatencyUnit::Millis)),
+        )
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(cors)
+        .with_state(state)
+}
+
+/// The process is up and serving; dependencies are deliberately not checked here,
+/// so a flaky RPC provider never gets the process restarted
+async fn liveness_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": CheckStatus::Pass }))
+}
+
+/// Probe every dependency; 503 while any of them fails so load balancers route elsewhere
+async fn readiness_handler(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let ws_url = state.rpc_pool.primary().ws_url.clone();
+    let (mut checks, websocket, payer_balance) = tokio::join!(
+        health::check_rpc(&state.rpc_client, &state.slot_tracker),
+        state.websocket_check.run("websocket", health::WEBSOCKET_CHECK_TTL, health::check_websocket(&ws_url)),
+        health::timed(
+            "payer_balance",
+            health::check_payer(&state.rpc_client, &state.payer, state.config.payer.low_balance_sol),
+        ),
+    );
+    checks.push(websocket);
+    checks.push(payer_balance);
+    
+    let rpc_endpoints = state.rpc_pool.stats();
+    checks.push(health::timed("rpc_endpoints", async { health::rpc_endpoints(&rpc_endpoints) }).await);
+    checks.push(
+        health::timed("vanity_pools", async { health::vanity_pools(&state.vanity_service.stats().await) }).await,
+    );
+    checks.push(
+        health::timed("accepting_trades", async {
+            if state.shutdown.is_triggered() {
+                (CheckStatus::Fail, "shutting down".to_string())
+            } else {
+                (CheckStatus::Pass, format!("{} trades in flight", state.shutdown.in_flight()))
+            }
+        })
+        .await,
+    );
+    
+    for check in checks.iter().filter(|c| c.status != CheckStatus::Pass) {
+        warn!("Readiness check {} is {:?}: {}", check.name, check.status, check.detail);
+    }
+    
+    let status = health::overall(&checks);
+    let code = if status == CheckStatus::Fail { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+    (
+        code,
+        Json(ReadinessResponse {
+            status,
+            cluster: state.config.cluster.to_string(),
+            payer: state.payer.to_string(),
+            checks,
+            rpc_endpoints,
+        }),
+    )
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    // Pool sizes are sampled at scrape time rather than on every take/refill
+    for pool in state.vanity_service.stats().await {
+        metrics().vanity_pool_size
+            .with_label_values(&[&pool.name, &pool.pattern])
+            .set(pool.pool_size as i64);
+        metrics().vanity_pool_target
+            .with_label_values(&[&pool.name, &pool.pattern])
+            .set(pool.target_size as i64);
+    }
+    
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(),
+    )
+}
+
+async fn vanity_stats_handler(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let pools = state.vanity_service.stats().await;
+    let default_pool = state.vanity_service.default_pool_name();
+    let default_stats = pools.iter().find(|p| p.name == default_pool);
+    
+    Ok(Json(serde_json::json!({
+        "pool_size": default_stats.map(|p| p.pool_size).unwrap_or(0),
+        "suffix": default_stats.map(|p| p.pattern.as_str()).unwrap_or_default(),
+        "default_pool": default_pool,
+        "pools": pools,
+        "reserved": state.vanity_reservations.held().await,
+        "workers": state.vanity_ingest.stats(),
+    })))
+}
+
+/// Keypairs pushed by a remote grinder. The sealed batch is the authentication:
+/// it only opens with the key configured for the worker it names.
+async fn vanity_ingest_handler(
+    State(state): State<AppState>,
+    Json(envelope): Json<IngestEnvelope>,
+) -> Result<Json<IngestOutcome>, StatusCode> {
+    let batch = state.vanity_ingest.open(&envelope).map_err(|e| {
+        warn!("Rejected vanity batch from worker {}: {}", envelope.worker, e);
+        ingest_error_status(&e)
+    })?;
+    let (pool, attempts, elapsed_secs) = (batch.pool, batch.attempts, batch.elapsed_secs);
+    
+    let outcome = state.vanity_service.ingest(&pool, batch.keypairs).await.map_err(|e| {
+        warn!("Rejected vanity batch from worker {}: {}", envelope.worker, e);
+        ingest_error_status(&e)
+    })?;
+    state.vanity_ingest.record(&envelope.worker, attempts, elapsed_secs, &outcome);
+    info!(
+        "Worker {} added {} keypairs to pool '{}' ({} duplicate, {} invalid, {} surplus; now {}/{})",
+        envelope.worker, outcome.accepted, pool, outcome.duplicates, outcome.invalid, outcome.surplus,
+        outcome.pool_size, outcome.target_size
+    );
+    Ok(Json(outcome))
+}
+
+pub(crate) fn reservation_error_status(error: &ReservationError) -> StatusCode {
+    match error {
+        ReservationError::UnknownPool(_) => StatusCode::BAD_REQUEST,
+        ReservationError::NotFound => StatusCode::NOT_FOUND,
+        ReservationError::PoolEmpty(_) => StatusCode::SERVICE_UNAVAILABLE,
+        ReservationError::TooManyReservations(_) => StatusCode::TOO_MANY_REQUESTS,
+        ReservationError::NotOwner => StatusCode::FORBIDDEN,
+        ReservationError::Expired => StatusCode::GONE,
+        ReservationError::InUse => StatusCode::CONFLICT,
+    }
+}
+
+pub(crate) fn ingest_error_status(error: &IngestError) -> StatusCode {
+    match error {
+        IngestError::UnknownWorker(_) | IngestError::Decrypt => StatusCode::UNAUTHORIZED,
+        IngestError::Malformed(_) => StatusCode::BAD_REQUEST,
+        IngestError::UnknownPool(_) => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Hold the next address of a pool for the wallet so the launch modal can show it;
+/// the create call spends it by passing `vanity_reservation_id`
+async fn reserve_vanity_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ReserveVanityRequest>,
+) -> Result<(StatusCode, Json<ReservationView>), StatusCode> {
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    logging::record_wallet(&request.wallet_address);
+    
+    match state.vanity_reservations.reserve(&request.wallet_address, request.pool.as_deref()).await {
+        Ok(reservation) => Ok((StatusCode::CREATED, Json(reservation))),
+        Err(e) => {
+            warn!("Rejected vanity reservation for {}: {}", request.wallet_address, e);
+            Err(reservation_error_status(&e))
+        }
+    }
+}
+
+async fn create_vanity_job_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateVanityJobRequest>,
+) -> Result<(StatusCode, Json<JobView>), StatusCode> {
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    logging::record_wallet(&request.wallet_address);
+    
+    match state.vanity_jobs.submit(&request.wallet_address, &request.pattern).await {
+        Ok(job) => Ok((StatusCode::ACCEPTED, Json(job))),
+        Err(e) => {
+            warn!("Rejected vanity job for {}: {}", request.wallet_address, e);
+            Err(job_error_status(&e))
+        }
+    }
+}
+
+async fn get_vanity_job_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<JobView>, StatusCode> {
+    state.vanity_jobs.get(&id).await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn cancel_vanity_job_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<auth::WalletAuthRequest>,
+) -> Result<Json<JobView>, StatusCode> {
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    logging::record_wallet(&request.wallet_address);
+    
+    state.vanity_jobs.cancel(&id, &request.wallet_address).await
+        .map(Json)
+        .map_err(|e| job_error_status(&e))
+}
+
+pub(crate) fn job_error_status(error: &JobError) -> StatusCode {
+    match error {
+        JobError::InvalidPattern(_) => StatusCode::BAD_REQUEST,
+        JobError::TooDifficult { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        JobError::TooManyJobs(_) => StatusCode::TOO_MANY_REQUESTS,
+        JobError::QueueFull(_) | JobError::StoreFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+        JobError::NotFound => StatusCode::NOT_FOUND,
+        JobError::NotOwner => StatusCode::FORBIDDEN,
+        JobError::NotReady | JobError::AlreadyClaimed | JobError::InUse => StatusCode::CONFLICT,
+    }
+}
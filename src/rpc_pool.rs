@@ -0,0 +1,655 @@
+use crate::config::redact_url;
+use crate::metrics::metrics;
+use async_trait::async_trait;
+use futures::future::join_all;
+use futures::stream::{FuturesUnordered, StreamExt};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::client_error::{reqwest, ClientError, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+};
+use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Per-request timeout before an endpoint counts as failed
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// First cooldown after a failure; doubles per consecutive failure up to [`MAX_COOLDOWN`]
+const BASE_COOLDOWN: Duration = Duration::from_secs(1);
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Weight given to each new latency sample in the moving average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// One JSON-RPC provider
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcEndpoint {
+    pub url: String,
+    pub ws_url: String,
+    /// Relative share of reads this endpoint receives while healthy
+    pub weight: u32,
+}
+
+impl RpcEndpoint {
+    /// Parse `RPC_ENDPOINTS`, e.g. `https://a.example|wss://a.example|3,https://b.example|wss://b.example`.
+    /// The weight is optional and defaults to 1.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        let mut endpoints = Vec::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split('|').map(str::trim).collect();
+            let (url, ws_url, weight) = match parts.as_slice() {
+                [url, ws_url] => (url, ws_url, 1),
+                [url, ws_url, weight] => (
+                    url,
+                    ws_url,
+                    weight
+                        .parse()
+                        .map_err(|_| format!("invalid weight '{}' for {}", weight, redact_url(url)))?,
+                ),
+                _ => {
+                    return Err(format!(
+                        "invalid RPC endpoint '{}', expected http_url|ws_url[|weight]",
+                        redact_url(entry)
+                    ))
+                }
+            };
+            endpoints.push(RpcEndpoint {
+                url: url.to_string(),
+                ws_url: ws_url.to_string(),
+                weight,
+            });
+        }
+        if endpoints.is_empty() {
+            return Err("no RPC endpoints given".to_string());
+        }
+        Ok(endpoints)
+    }
+}
+
+/// Health and traffic counters for one endpoint, as reported by /health
+#[derive(Serialize, Debug, Clone)]
+pub struct EndpointStats {
+    pub url: String,
+    pub weight: u32,
+    pub healthy: bool,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub latency_ms: f64,
+    /// Seconds until a cooling-down endpoint is tried again
+    pub cooldown_secs: Option<f64>,
+}
+
+struct EndpointState {
+    endpoint: RpcEndpoint,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    consecutive_failures: AtomicU32,
+    /// Smoothed latency of successful requests, in microseconds (0 = no sample yet)
+    latency_us: AtomicU64,
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl EndpointState {
+    fn new(endpoint: RpcEndpoint) -> Self {
+        Self {
+            endpoint,
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            latency_us: AtomicU64::new(0),
+            cooldown_until: Mutex::new(None),
+        }
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.cooldown_until.lock().unwrap().is_none_or(|until| until <= now)
+    }
+
+    fn record_success(&self, elapsed: Duration) {
+        let sample = elapsed.as_micros() as f64;
+        let previous = self.latency_us.load(Ordering::Relaxed);
+        let smoothed = if previous == 0 {
+            sample
+        } else {
+            previous as f64 * (1.0 - LATENCY_SMOOTHING) + sample * LATENCY_SMOOTHING
+        };
+        self.latency_us.store(smoothed.max(1.0) as u64, Ordering::Relaxed);
+
+        if self.consecutive_failures.swap(0, Ordering::Relaxed) > 0 {
+            info!("RPC endpoint {} recovered", redact_url(&self.endpoint.url));
+        }
+        *self.cooldown_until.lock().unwrap() = None;
+    }
+
+    /// Bench the endpoint for `retry_after` if the server asked for it, otherwise back off exponentially
+    fn record_failure(&self, retry_after: Option<Duration>) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        let backoff = BASE_COOLDOWN.saturating_mul(1 << (failures - 1).min(6)).min(MAX_COOLDOWN);
+        let cooldown = retry_after.unwrap_or(backoff).min(MAX_COOLDOWN);
+        *self.cooldown_until.lock().unwrap() = Some(Instant::now() + cooldown);
+    }
+
+    /// Higher is better: weight per millisecond of latency
+    fn score(&self) -> f64 {
+        let latency_ms = (self.latency_us.load(Ordering::Relaxed) as f64 / 1_000.0).max(1.0);
+        self.endpoint.weight as f64 / latency_ms
+    }
+
+    fn stats(&self, now: Instant) -> EndpointStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let errors = self.errors.load(Ordering::Relaxed);
+        let cooldown_secs = self
+            .cooldown_until
+            .lock()
+            .unwrap()
+            .filter(|until| *until > now)
+            .map(|until| (until - now).as_secs_f64());
+        EndpointStats {
+            url: redact_url(&self.endpoint.url),
+            weight: self.endpoint.weight,
+            healthy: cooldown_secs.is_none(),
+            requests,
+            errors,
+            error_rate: if requests == 0 { 0.0 } else { errors as f64 / requests as f64 },
+            latency_ms: self.latency_us.load(Ordering::Relaxed) as f64 / 1_000.0,
+            cooldown_secs,
+        }
+    }
+}
+
+enum Outcome {
+    Ok(Value),
+    /// The node answered with a JSON-RPC error; another node would say the same
+    Rejected(ClientError),
+    /// Transport failure, throttling or an unhealthy node; worth trying elsewhere
+    Failed(ClientError),
+}
+
+struct PoolInner {
+    endpoints: Vec<EndpointState>,
+    http: reqwest::Client,
+    request_id: AtomicU64,
+    transport: RwLock<RpcTransportStats>,
+}
+
+/// Transport for [`RpcClient`] that spreads reads over several endpoints and fails over
+/// on 429s, 5xx responses, timeouts and unhealthy nodes. `sendTransaction` goes to every
+/// healthy endpoint at once so a transaction lands even if one provider drops it.
+#[derive(Clone)]
+pub struct RpcPool {
+    inner: Arc<PoolInner>,
+}
+
+impl RpcPool {
+    pub fn new(endpoints: Vec<RpcEndpoint>) -> Self {
+        Self::with_timeout(endpoints, DEFAULT_REQUEST_TIMEOUT)
+    }
+
+    pub fn with_timeout(endpoints: Vec<RpcEndpoint>, timeout: Duration) -> Self {
+        assert!(!endpoints.is_empty(), "RpcPool needs at least one endpoint");
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to build RPC HTTP client");
+        Self {
+            inner: Arc::new(PoolInner {
+                endpoints: endpoints.into_iter().map(EndpointState::new).collect(),
+                http,
+                request_id: AtomicU64::new(0),
+                transport: RwLock::new(RpcTransportStats::default()),
+            }),
+        }
+    }
+
+    /// An [`RpcClient`] whose requests all go through this pool
+    pub fn client(&self, commitment: CommitmentConfig) -> RpcClient {
+        RpcClient::new_sender(self.clone(), RpcClientConfig::with_commitment(commitment))
+    }
+
+    /// The first configured endpoint, used where a single URL is required (websockets)
+    pub fn primary(&self) -> &RpcEndpoint {
+        &self.inner.endpoints[0].endpoint
+    }
+
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        let now = Instant::now();
+        self.inner.endpoints.iter().map(|e| e.stats(now)).collect()
+    }
+
+    /// Probe every endpoint with `getHealth`, benching the ones that fail
+    pub async fn check_health(&self) {
+        let body = RpcRequest::GetHealth
+            .build_request_json(self.next_id(), Value::Null)
+            .to_string();
+        let probes = (0..self.inner.endpoints.len()).map(|idx| self.send_one(idx, RpcRequest::GetHealth, &body));
+        for (state, outcome) in self.inner.endpoints.iter().zip(join_all(probes).await) {
+            if let Outcome::Failed(e) = outcome {
+                warn!("RPC endpoint {} failed its health check: {}", redact_url(&state.endpoint.url), e);
+            }
+        }
+    }
+
+    pub fn spawn_health_checks(&self, every: Duration) {
+        let pool = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                pool.check_health().await;
+            }
+        });
+    }
+
+    fn next_id(&self) -> u64 {
+        self.inner.request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Healthy endpoints in weighted-random order (favouring low latency), then benched
+    /// ones by how soon their cooldown ends as a last resort
+    fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut rng = rand::thread_rng();
+        let (healthy, benched): (Vec<usize>, Vec<usize>) =
+            (0..self.inner.endpoints.len()).partition(|&idx| self.inner.endpoints[idx].is_healthy(now));
+
+        // Weighted sampling without replacement: sort by u^(1/score)
+        let mut keyed: Vec<(f64, usize)> = healthy
+            .into_iter()
+            .map(|idx| {
+                let score = self.inner.endpoints[idx].score().max(f64::MIN_POSITIVE);
+                (rng.gen::<f64>().powf(1.0 / score), idx)
+            })
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut benched: Vec<(Instant, usize)> = benched
+            .into_iter()
+            .map(|idx| (self.inner.endpoints[idx].cooldown_until.lock().unwrap().unwrap_or(now), idx))
+            .collect();
+        benched.sort();
+
+        keyed.into_iter().map(|(_, idx)| idx).chain(benched.into_iter().map(|(_, idx)| idx)).collect()
+    }
+
+    async fn send_one(&self, idx: usize, request: RpcRequest, body: &str) -> Outcome {
+        let state = &self.inner.endpoints[idx];
+        state.requests.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+
+        let (outcome, error_kind) = self.exchange(state, body, start).await;
+
+        let endpoint = redact_url(&state.endpoint.url);
+        let metrics = metrics();
+        metrics
+            .rpc_latency
+            .with_label_values(&[&endpoint, &request.to_string()])
+            .observe(start.elapsed().as_secs_f64());
+        if let Some(kind) = error_kind {
+            metrics.rpc_errors.with_label_values(&[&endpoint, kind]).inc();
+        }
+        outcome
+    }
+
+    /// One HTTP round trip, with the error kind reported to metrics
+    async fn exchange(&self, state: &EndpointState, body: &str, start: Instant) -> (Outcome, Option<&'static str>) {
+
+        let response = match self
+            .inner
+            .http
+            .post(&state.endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                state.record_failure(None);
+                let kind = if e.is_timeout() { "timeout" } else { "transport" };
+                return (Outcome::Failed(e.into()), Some(kind));
+            }
+        };
+
+        if !response.status().is_success() {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs);
+            state.record_failure(retry_after);
+            let kind = match response.status().as_u16() {
+                429 => "http_429",
+                500..=599 => "http_5xx",
+                _ => "http_other",
+            };
+            return (Outcome::Failed(response.error_for_status().unwrap_err().into()), Some(kind));
+        }
+
+        let mut json: Value = match response.json().await {
+            Ok(json) => json,
+            Err(e) => {
+                state.record_failure(None);
+                return (Outcome::Failed(e.into()), Some("decode"));
+            }
+        };
+        if json["error"].is_object() {
+            let (code, error) = rpc_error(&json["error"]);
+            if code == Some(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY) {
+                state.record_failure(None);
+                return (Outcome::Failed(error), Some("node_unhealthy"));
+            }
+            state.record_success(start.elapsed());
+            return (Outcome::Rejected(error), Some("rpc_error"));
+        }
+
+        state.record_success(start.elapsed());
+        (Outcome::Ok(json["result"].take()), None)
+    }
+
+    async fn failover(&self, request: RpcRequest, body: &str) -> ClientResult<Value> {
+        let mut last_error = None;
+        for idx in self.candidates() {
+            match self.send_one(idx, request, body).await {
+                Outcome::Ok(result) => return Ok(result),
+                Outcome::Rejected(e) => return Err(e),
+                Outcome::Failed(e) => {
+                    warn!(
+                        "RPC {} failed on {}: {}",
+                        request,
+                        redact_url(&self.inner.endpoints[idx].endpoint.url),
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| RpcError::RpcRequestError("no RPC endpoints available".to_string()).into()))
+    }
+
+    /// Send to every healthy endpoint (or all of them if none are healthy); the first
+    /// acceptance wins. The other sends run on as their own tasks, so a slow endpoint
+    /// only delays its own stats, not the caller.
+    async fn broadcast(&self, body: &str) -> ClientResult<Value> {
+        let now = Instant::now();
+        let mut targets: Vec<usize> = (0..self.inner.endpoints.len())
+            .filter(|&idx| self.inner.endpoints[idx].is_healthy(now))
+            .collect();
+        if targets.is_empty() {
+            targets = (0..self.inner.endpoints.len()).collect();
+        }
+
+        let mut sends: FuturesUnordered<_> = targets
+            .into_iter()
+            .map(|idx| {
+                let (pool, body) = (self.clone(), body.to_string());
+                tokio::spawn(async move { pool.send_one(idx, RpcRequest::SendTransaction, &body).await })
+            })
+            .collect();
+        let mut rejected = None;
+        let mut failed = None;
+        while let Some(joined) = sends.next().await {
+            match joined {
+                Ok(Outcome::Ok(result)) => return Ok(result),
+                Ok(Outcome::Rejected(e)) => rejected = rejected.or(Some(e)),
+                Ok(Outcome::Failed(e)) => failed = Some(e),
+                Err(e) => warn!("RPC send task failed: {}", e),
+            }
+        }
+        // A preflight rejection explains more than a transport error from another node
+        Err(rejected
+            .or(failed)
+            .unwrap_or_else(|| RpcError::RpcRequestError("no RPC endpoints available".to_string()).into()))
+    }
+}
+
+#[async_trait]
+impl RpcSender for RpcPool {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let start = Instant::now();
+        let body = request.build_request_json(self.next_id(), params).to_string();
+        let result = if request == RpcRequest::SendTransaction {
+            self.broadcast(&body).await
+        } else {
+            self.failover(request, &body).await
+        };
+
+        let mut transport = self.inner.transport.write().unwrap();
+        transport.request_count += 1;
+        transport.elapsed_time += start.elapsed();
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.transport.read().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        self.primary().url.clone()
+    }
+}
+
+/// Convert a JSON-RPC error object the same way the stock HTTP sender does
+fn rpc_error(error: &Value) -> (Option<i64>, ClientError) {
+    let code = error["code"].as_i64();
+    let message = error["message"].as_str().map(str::to_string);
+    match (code, message) {
+        (Some(code), Some(message)) => {
+            let data = if code == JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE {
+                serde_json::from_value::<RpcSimulateTransactionResult>(error["data"].clone())
+                    .map(RpcResponseErrorData::SendTransactionPreflightFailure)
+                    .unwrap_or(RpcResponseErrorData::Empty)
+            } else {
+                RpcResponseErrorData::Empty
+            };
+            (Some(code), RpcError::RpcResponseError { code, message, data }.into())
+        }
+        _ => (
+            code,
+            RpcError::RpcRequestError(format!("Failed to deserialize RPC error response: {}", error)).into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Clone, Copy)]
+    enum Behaviour {
+        Ok,
+        TooManyRequests,
+        ServerError,
+        Slow,
+        NodeUnhealthy,
+        InvalidParams,
+    }
+
+    struct MockNode {
+        url: String,
+        hits: Arc<AtomicUsize>,
+    }
+
+    /// Local JSON-RPC server answering every call according to `behaviour`
+    async fn mock_node(behaviour: Behaviour) -> MockNode {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/",
+                post(|State((behaviour, hits)): State<(Behaviour, Arc<AtomicUsize>)>, Json(req): Json<Value>| async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    let id = req["id"].clone();
+                    let error = |code: i64, message: &str| {
+                        Json(serde_json::json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}}))
+                    };
+                    match behaviour {
+                        Behaviour::Ok => {
+                            let result = match req["method"].as_str() {
+                                Some("sendTransaction") => serde_json::json!("signature"),
+                                Some("getHealth") => serde_json::json!("ok"),
+                                Some("getVersion") => serde_json::json!({"solana-core": "2.3.0", "feature-set": 0}),
+                                _ => serde_json::json!(42),
+                            };
+                            Json(serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result})).into_response()
+                        }
+                        Behaviour::TooManyRequests => (StatusCode::TOO_MANY_REQUESTS, "slow down").into_response(),
+                        Behaviour::ServerError => (StatusCode::BAD_GATEWAY, "upstream down").into_response(),
+                        Behaviour::Slow => {
+                            tokio::time::sleep(Duration::from_secs(5)).await;
+                            StatusCode::OK.into_response()
+                        }
+                        Behaviour::NodeUnhealthy => error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, "Node is behind").into_response(),
+                        Behaviour::InvalidParams => error(-32602, "Invalid params").into_response(),
+                    }
+                }),
+            )
+            .with_state((behaviour, hits.clone()));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        MockNode {
+            url: format!("http://{}", addr),
+            hits,
+        }
+    }
+
+    fn pool(nodes: &[&MockNode]) -> RpcPool {
+        let endpoints = nodes
+            .iter()
+            .map(|node| RpcEndpoint {
+                url: node.url.clone(),
+                ws_url: node.url.replace("http://", "ws://"),
+                weight: 1,
+            })
+            .collect();
+        RpcPool::with_timeout(endpoints, Duration::from_millis(300))
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_benches_bad_endpoints() {
+        for behaviour in [Behaviour::TooManyRequests, Behaviour::ServerError, Behaviour::Slow, Behaviour::NodeUnhealthy] {
+            let bad = mock_node(behaviour).await;
+            let good = mock_node(Behaviour::Ok).await;
+            let pool = pool(&[&bad, &good]);
+            let client = pool.client(CommitmentConfig::confirmed());
+
+            // Reads are spread randomly, so keep going until the bad endpoint has been picked
+            for _ in 0..100 {
+                assert_eq!(client.get_slot().await.unwrap(), 42);
+                if bad.hits.load(Ordering::SeqCst) > 0 {
+                    break;
+                }
+            }
+            assert_eq!(bad.hits.load(Ordering::SeqCst), 1);
+
+            // Once benched, the bad endpoint is not retried on every call
+            let good_before = good.hits.load(Ordering::SeqCst);
+            for _ in 0..5 {
+                assert_eq!(client.get_slot().await.unwrap(), 42);
+            }
+            assert_eq!(bad.hits.load(Ordering::SeqCst), 1);
+            assert_eq!(good.hits.load(Ordering::SeqCst) - good_before, 5);
+
+            let stats = pool.stats();
+            assert!(!stats[0].healthy);
+            assert!(stats[1].healthy);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_errors_are_not_retried() {
+        let rejecting = mock_node(Behaviour::InvalidParams).await;
+        let other = mock_node(Behaviour::InvalidParams).await;
+        let client = pool(&[&rejecting, &other]).client(CommitmentConfig::confirmed());
+
+        assert!(client.get_slot().await.is_err());
+        assert_eq!(rejecting.hits.load(Ordering::SeqCst) + other.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_goes_to_every_healthy_endpoint() {
+        let a = mock_node(Behaviour::Ok).await;
+        let b = mock_node(Behaviour::Ok).await;
+        let down = mock_node(Behaviour::ServerError).await;
+        let pool = pool(&[&a, &b, &down]);
+
+        let body = RpcRequest::SendTransaction.build_request_json(1, serde_json::json!(["tx"])).to_string();
+        assert_eq!(pool.broadcast(&body).await.unwrap(), serde_json::json!("signature"));
+        // The broadcast returns on the first acceptance; the other sends finish on their own
+        wait_until(|| !pool.stats()[2].healthy).await;
+        assert_eq!(a.hits.load(Ordering::SeqCst), 1);
+        assert_eq!(b.hits.load(Ordering::SeqCst), 1);
+        assert_eq!(down.hits.load(Ordering::SeqCst), 1);
+
+        // The failed endpoint sits out the next broadcast
+        pool.broadcast(&body).await.unwrap();
+        assert_eq!(down.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_does_not_wait_for_slow_endpoints() {
+        let slow = mock_node(Behaviour::Slow).await;
+        let fast = mock_node(Behaviour::Ok).await;
+        let pool = pool(&[&slow, &fast]);
+
+        let body = RpcRequest::SendTransaction.build_request_json(1, serde_json::json!(["tx"])).to_string();
+        let started = Instant::now();
+        assert_eq!(pool.broadcast(&body).await.unwrap(), serde_json::json!("signature"));
+        // Well inside the 300ms request timeout the slow endpoint runs into
+        assert!(started.elapsed() < Duration::from_millis(200), "took {:?}", started.elapsed());
+
+        // Its timeout is still recorded, just not on the caller's time
+        wait_until(|| !pool.stats()[0].healthy).await;
+    }
+
+    async fn wait_until(done: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("condition not reached within 2s");
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_down_returns_error() {
+        let a = mock_node(Behaviour::ServerError).await;
+        let b = mock_node(Behaviour::TooManyRequests).await;
+        let pool = pool(&[&a, &b]);
+        assert!(pool.client(CommitmentConfig::confirmed()).get_slot().await.is_err());
+
+        // Benched endpoints are still tried as a last resort
+        pool.check_health().await;
+        assert_eq!(a.hits.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.stats().iter().filter(|s| s.healthy).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_endpoint_list() {
+        let endpoints = RpcEndpoint::parse_list("https://a.example|wss://a.example|3, https://b.example|wss://b.example").unwrap();
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].weight, 3);
+        assert_eq!(endpoints[1].weight, 1);
+        assert_eq!(endpoints[1].ws_url, "wss://b.example");
+
+        assert!(RpcEndpoint::parse_list("https://a.example").is_err());
+        assert!(RpcEndpoint::parse_list("https://a.example|wss://a.example|heavy").is_err());
+        assert!(RpcEndpoint::parse_list("").is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! SIGINT/SIGTERM handling and draining of in-flight trades.
+//!
+//! Trade handlers hold an [`InFlightGuard`] from submission until the SDK returns
+//! (which includes waiting for confirmation), so shutdown can wait for them
+//! before the process exits.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tracing::{info, warn};
+
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    triggered: watch::Sender<bool>,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                triggered: watch::Sender::new(false),
+                in_flight: AtomicUsize::new(0),
+                idle: Notify::new(),
+            }),
+        }
+    }
+
+    /// Begin shutting down; idempotent
+    pub fn trigger(&self) {
+        self.inner.triggered.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.inner.triggered.borrow()
+    }
+
+    /// Resolves once [`Shutdown::trigger`] has been called
+    pub async fn triggered(&self) {
+        let mut rx = self.inner.triggered.subscribe();
+        // The sender lives in `self`, so this only errors if it was dropped mid-wait
+        let _ = rx.wait_for(|triggered| *triggered).await;
+    }
+
+    /// Trigger on the first SIGINT (Ctrl-C) or SIGTERM
+    pub fn spawn_signal_listener(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            let signal = wait_for_signal().await;
+            info!("Received {}, shutting down", signal);
+            shutdown.trigger();
+        });
+    }
+
+    /// Register a trade in flight. Returns `None` once shutdown has started,
+    /// so new submissions are refused instead of being cut off mid-way.
+    pub fn track(&self) -> Option<InFlightGuard> {
+        // Count first so a concurrent drain never sees zero while this trade proceeds
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlightGuard(self.inner.clone());
+        if self.is_triggered() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no trades are in flight or `timeout` passes. Returns whether everything drained.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let wait_idle = async {
+            loop {
+                let idle = self.inner.idle.notified();
+                tokio::pin!(idle);
+                // Register interest before checking, so a guard dropped in between is not missed
+                idle.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        match tokio::time::timeout(timeout, wait_idle).await {
+            Ok(()) => true,
+            Err(_) => {
+                warn!("{} trades still in flight after {:?}", self.in_flight(), timeout);
+                false
+            }
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marks one trade as in flight until dropped
+pub struct InFlightGuard(Arc<Inner>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+        _ = sigterm.recv() => "SIGTERM",
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl-C"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_trades() {
+        let shutdown = Shutdown::new();
+        let guard = shutdown.track().expect("accepting before shutdown");
+        assert_eq!(shutdown.in_flight(), 1);
+
+        shutdown.trigger();
+        assert!(shutdown.track().is_none());
+        assert_eq!(shutdown.in_flight(), 1);
+        assert!(!shutdown.drain(Duration::from_millis(20)).await);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(shutdown.drain(Duration::from_secs(5)).await);
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_triggered_resolves_after_trigger() {
+        let shutdown = Shutdown::new();
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.triggered().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        // Late subscribers see the current state
+        shutdown.triggered().await;
+    }
+}
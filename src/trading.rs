@@ -0,0 +1,567 @@
+use crate::metrics::metrics;
+use crate::pump::{self, CreateArgs, MintProgram, SubmitError};
+use crate::pump_trade::{self, Purchase, Route, TradeError, Venue};
+use crate::routes::{job_error_status, reservation_error_status};
+use crate::{auth, logging, AppState};
+use axum::{extract::State, http::StatusCode, response::Json};
+use pumpfun::utils::CreateTokenMetadata;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use tracing::{info, warn};
+
+#[derive(Deserialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub image_path: Option<String>,
+    // Metadata JSON the client already uploaded; skips the upload to pump.fun
+    pub metadata_uri: Option<String>,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+    pub track_volume: Option<bool>,
+    pub use_vanity: Option<bool>,
+    // Named vanity pool to draw from (defaults to the service's default pool)
+    pub vanity_pool: Option<String>,
+    // Completed custom vanity job reserved for this wallet
+    pub vanity_job_id: Option<String>,
+    // Address held for this wallet by POST /vanity/reserve
+    pub vanity_reservation_id: Option<String>,
+    // Only read to refuse it: mint secret keys never travel over HTTP (see /tx/prepare-create)
+    pub pump_private_key: Option<String>,
+    // Wallet integration fields
+    pub wallet_address: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAndBuyRequest {
+    #[serde(flatten)]
+    pub create: CreateTokenRequest,
+    pub amount_sol: f64,
+    pub slippage_bps: Option<u16>,
+}
+
+/// Create with a mint keypair the client generated and keeps
+#[derive(Deserialize)]
+pub struct PrepareCreateRequest {
+    /// Public key of the client's mint keypair
+    pub mint: String,
+    #[serde(flatten)]
+    pub create: CreateTokenRequest,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitTransactionRequest {
+    /// Base64 bincode transaction from /tx/prepare-create, now signed by the mint
+    pub transaction: String,
+}
+
+#[derive(Deserialize)]
+pub struct BuyTokenRequest {
+    pub mint: String,
+    pub amount_sol: f64,
+    pub track_volume: Option<bool>,
+    pub slippage_bps: Option<u16>,
+    // Wallet integration fields
+    pub wallet_address: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct SellTokenRequest {
+    pub mint: String,
+    pub amount_tokens: Option<u64>,
+    pub sell_all: Option<bool>,
+    pub slippage_bps: Option<u16>,
+    // Wallet integration fields
+    pub wallet_address: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct TransactionResponse {
+    pub signature: String,
+    pub mint: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PreparedTransactionResponse {
+    /// Base64 bincode transaction signed by the fee payer; the mint signs it before /tx/submit
+    pub transaction: String,
+    pub mint: String,
+    /// The transaction can no longer land once the chain passes this height
+    pub last_valid_block_height: u64,
+}
+
+
+pub async fn create_token_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateTokenRequest>,
+) -> Result<Json<TransactionResponse>, StatusCode> {
+    // Verify wallet signature
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    
+    // Refuse new trades once shutdown has started; the guard lets shutdown wait for this one
+    let _in_flight = state.shutdown.track().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    
+    logging::record_wallet(&request.wallet_address);
+    info!("Creating token: {} ({}) for wallet: {}", request.name, request.symbol, request.wallet_address);
+    reject_private_key(&request)?;
+    let creator: Pubkey = request.wallet_address.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    // Get mint keypair from a reservation, a vanity job, a vanity pool or a fresh random one
+    let mint = if let Some(reservation_id) = &request.vanity_reservation_id {
+        // Address the launch modal has already shown, checked out until the create ends
+        state.vanity_reservations.checkout(reservation_id, &request.wallet_address).await
+            .map_err(|e| {
+                warn!("Cannot use vanity reservation {}: {}", reservation_id, e);
+                reservation_error_status(&e)
+            })?
+    } else if let Some(job_id) = &request.vanity_job_id {
+        // Custom vanity job reserved for this wallet, checked out until the create ends
+        state.vanity_jobs.checkout(job_id, &request.wallet_address).await
+            .map_err(|e| {
+                warn!("Cannot use vanity job {}: {}", job_id, e);
+                job_error_status(&e)
+            })?
+    } else if request.use_vanity.unwrap_or(true) {
+        // Fallback to vanity service
+        next_vanity_keypair(&state, request.vanity_pool.as_deref()).await?
+    } else {
+        Keypair::new()
+    };
+    
+    let mint_pubkey = mint.pubkey();
+    logging::record_mint(&mint_pubkey);
+    
+    // Create token
+    let _pending = metrics().pending_transaction();
+    let mut may_have_landed = false;
+    let result = async {
+        let args = launch_args(&request, creator).await?;
+        pump_trade::create(&state.rpc_client, &state.payer_signer, &mint, &args, state.config.mint_program)
+            .await
+            .map_err(|e| {
+                may_have_landed = e.may_have_landed();
+                e.to_string()
+            })
+    }
+    .await;
+    metrics().record_trade("create", &result);
+    // A pool-drawn mint is spent either way: used on-chain, or dropped below
+    state.vanity_service.mark_used(&mint_pubkey);
+    // A checked-out key whose launch landed or may still land is spent; otherwise the wallet can retry with it
+    let spent = result.is_ok() || may_have_landed;
+    if let Some(reservation_id) = &request.vanity_reservation_id {
+        if spent {
+            state.vanity_reservations.mark_claimed(reservation_id).await;
+        } else {
+            state.vanity_reservations.restore(reservation_id, mint).await;
+        }
+    } else if let Some(job_id) = &request.vanity_job_id {
+        if spent {
+            state.vanity_jobs.mark_claimed(job_id).await;
+        } else {
+            state.vanity_jobs.restore(job_id, mint).await;
+        }
+    }
+    
+    match result {
+        Ok(signature) => {
+            info!("Token created successfully: {}", signature);
+            Ok(Json(TransactionResponse {
+                signature: signature.to_string(),
+                mint: Some(mint_pubkey.to_string()),
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to create token: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn create_and_buy_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateAndBuyRequest>,
+) -> Result<Json<TransactionResponse>, StatusCode> {
+    // Verify wallet signature
+    if let Err(e) = auth::verify_wallet_signature(
+        &request.create.wallet_address,
+        &request.create.signature,
+        &request.create.message,
+    ) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    
+    // Refuse new trades once shutdown has started; the guard lets shutdown wait for this one
+    let _in_flight = state.shutdown.track().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    
+    logging::record_wallet(&request.create.wallet_address);
+    info!("Creating and buying token: {} ({})", request.create.name, request.create.symbol);
+    reject_private_key(&request.create)?;
+    check_slippage(request.slippage_bps)?;
+    
+    // Vanity jobs and reservations are held per wallet, so they can only be spent through /tx/create
+    if request.create.vanity_job_id.is_some() || request.create.vanity_reservation_id.is_some() {
+        warn!("Vanity job and reservation ids are only accepted by /tx/create");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    
+    let creator: Pubkey = request.create.wallet_address.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    // Get mint keypair from a vanity pool or a fresh random one
+    let mint = if request.create.use_vanity.unwrap_or(true) {
+        // Fallback to vanity service
+        next_vanity_keypair(&state, request.create.vanity_pool.as_deref()).await?
+    } else {
+        Keypair::new()
+    };
+    
+    let mint_pubkey = mint.pubkey();
+    logging::record_mint(&mint_pubkey);
+    
+    // Convert SOL to lamports
+    let lamports = (request.amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let purchase = Purchase {
+        lamports,
+        slippage_bps: request.slippage_bps,
+        track_volume: request.create.track_volume.unwrap_or(false),
+    };
+    
+    // Create and buy token
+    let _pending = metrics().pending_transaction();
+    let (rpc, payer, program) = (&state.rpc_client, &state.payer_signer, state.config.mint_program);
+    let result = async {
+        let args = launch_args(&request.create, creator).await?;
+        pump_trade::create_and_buy(rpc, payer, &mint, &args, program, purchase)
+            .await
+            .map_err(|e| e.to_string())
+    }
+    .await;
+    metrics().record_trade("create_and_buy", &result);
+    state.vanity_service.mark_used(&mint_pubkey);
+    match result {
+        Ok(signature) => {
+            info!("Token created and bought successfully: {}", signature);
+            Ok(Json(TransactionResponse {
+                signature: signature.to_string(),
+                mint: Some(mint_pubkey.to_string()),
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to create and buy token: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Build a create transaction for a mint keypair the client holds. The server signs as
+/// fee payer; the client adds the mint's signature and sends it back through /tx/submit.
+pub async fn prepare_create_handler(
+    State(state): State<AppState>,
+    Json(request): Json<PrepareCreateRequest>,
+) -> Result<Json<PreparedTransactionResponse>, StatusCode> {
+    let create = request.create;
+    if let Err(e) = auth::verify_wallet_signature(&create.wallet_address, &create.signature, &create.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    logging::record_wallet(&create.wallet_address);
+    logging::record_mint(&request.mint);
+    info!("Preparing token: {} ({}) for wallet: {}", create.name, create.symbol, create.wallet_address);
+    reject_private_key(&create)?;
+    
+    // Server-held mints (vanity pools, jobs, reservations) go through /tx/create instead
+    if create.vanity_job_id.is_some() || create.vanity_reservation_id.is_some() {
+        warn!("Vanity job and reservation ids are only accepted by /tx/create");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let mint: Pubkey = request.mint.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let creator: Pubkey = create.wallet_address.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    if mint == state.payer || mint == creator {
+        warn!("Mint {} must be a fresh keypair", mint);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    
+    // The launching wallet, not the server, earns the token's creator fees
+    let args = launch_args(&create, creator).await.map_err(|e| {
+        warn!("Failed to prepare token metadata: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let (blockhash, last_valid_block_height) = state
+        .rpc_client
+        .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+        .await
+        .map_err(|e| {
+            warn!("Failed to fetch a blockhash: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    
+    let transaction = pump::prepare_create(&state.payer_signer, &mint, &args, state.config.mint_program, blockhash);
+    Ok(Json(PreparedTransactionResponse {
+        transaction: pump::encode(&transaction),
+        mint: mint.to_string(),
+        last_valid_block_height,
+    }))
+}
+
+/// Send a transaction from /tx/prepare-create once the client has signed it
+pub async fn submit_transaction_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SubmitTransactionRequest>,
+) -> Result<Json<TransactionResponse>, StatusCode> {
+    let transaction = pump::decode_signed(&request.transaction, &state.payer).map_err(|e| {
+        warn!("Rejected submitted transaction: {}", e);
+        submit_error_status(&e)
+    })?;
+    
+    // Refuse new trades once shutdown has started; the guard lets shutdown wait for this one
+    let _in_flight = state.shutdown.track().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    
+    let _pending = metrics().pending_transaction();
+    let result = state.rpc_client.send_and_confirm_transaction(&transaction).await;
+    metrics().record_trade("submit", &result);
+    match result {
+        Ok(signature) => {
+            info!("Submitted transaction confirmed: {}", signature);
+            Ok(Json(TransactionResponse {
+                signature: signature.to_string(),
+                mint: None,
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to submit transaction: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn buy_token_handler(
+    State(state): State<AppState>,
+    Json(request): Json<BuyTokenRequest>,
+) -> Result<Json<TransactionResponse>, StatusCode> {
+    // Verify wallet signature
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    
+    // Refuse new trades once shutdown has started; the guard lets shutdown wait for this one
+    let _in_flight = state.shutdown.track().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    
+    logging::record_wallet(&request.wallet_address);
+    logging::record_mint(&request.mint);
+    info!("Buying token: {} for {} SOL by wallet: {}", request.mint, request.amount_sol, request.wallet_address);
+    check_slippage(request.slippage_bps)?;
+    
+    let mint_pubkey = request.mint.parse()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let route = trade_route(&state, &mint_pubkey).await?;
+    
+    let lamports = (request.amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let purchase = Purchase {
+        lamports,
+        slippage_bps: request.slippage_bps,
+        track_volume: request.track_volume.unwrap_or(false),
+    };
+    
+    let _pending = metrics().pending_transaction();
+    let (rpc, payer) = (&state.rpc_client, &state.payer_signer);
+    let result = match route {
+        Route { program: MintProgram::Spl, venue: Venue::BondingCurve } => state.pump_client.buy(
+            mint_pubkey,
+            lamports,
+            request.track_volume,
+            None, // slippage
+            None, // priority fee
+        ).await.map_err(|e| e.to_string()),
+        Route { program, venue: Venue::BondingCurve } => pump_trade::buy(rpc, payer, &mint_pubkey, program, purchase)
+            .await
+            .map_err(|e| e.to_string()),
+        Route { program, venue: Venue::PumpSwap } => pump_trade::swap_buy(rpc, payer, &mint_pubkey, program, purchase)
+            .await
+            .map_err(|e| e.to_string()),
+    };
+    metrics().record_trade("buy", &result);
+    match result {
+        Ok(signature) => {
+            info!("Token bought successfully on {}: {}", route.venue.as_str(), signature);
+            Ok(Json(TransactionResponse {
+                signature: signature.to_string(),
+                mint: None,
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to buy token: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn sell_token_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SellTokenRequest>,
+) -> Result<Json<TransactionResponse>, StatusCode> {
+    // Verify wallet signature
+    if let Err(e) = auth::verify_wallet_signature(&request.wallet_address, &request.signature, &request.message) {
+        warn!("Invalid wallet signature: {}", e);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    
+    // Refuse new trades once shutdown has started; the guard lets shutdown wait for this one
+    let _in_flight = state.shutdown.track().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    
+    logging::record_wallet(&request.wallet_address);
+    logging::record_mint(&request.mint);
+    info!("Selling token: {} by wallet: {}", request.mint, request.wallet_address);
+    check_slippage(request.slippage_bps)?;
+    
+    let mint_pubkey = request.mint.parse()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let route = trade_route(&state, &mint_pubkey).await?;
+    
+    let amount = if request.sell_all.unwrap_or(false) {
+        None
+    } else {
+        request.amount_tokens
+    };
+    
+    let _pending = metrics().pending_transaction();
+    let (rpc, payer, slippage) = (&state.rpc_client, &state.payer_signer, request.slippage_bps);
+    let result = match route {
+        Route { program: MintProgram::Spl, venue: Venue::BondingCurve } => state.pump_client.sell(
+            mint_pubkey,
+            amount,
+            None, // slippage
+            None, // priority fee
+        ).await.map_err(|e| e.to_string()),
+        Route { program, venue: Venue::BondingCurve } => pump_trade::sell(rpc, payer, &mint_pubkey, program, amount, slippage)
+            .await
+            .map_err(|e| e.to_string()),
+        Route { program, venue: Venue::PumpSwap } => pump_trade::swap_sell(rpc, payer, &mint_pubkey, program, amount, slippage)
+            .await
+            .map_err(|e| e.to_string()),
+    };
+    metrics().record_trade("sell", &result);
+    match result {
+        Ok(signature) => {
+            info!("Token sold successfully on {}: {}", route.venue.as_str(), signature);
+            Ok(Json(TransactionResponse {
+                signature: signature.to_string(),
+                mint: None,
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to sell token: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Slippage is a client-chosen u16; anything past 100% has no meaning
+fn check_slippage(slippage_bps: Option<u16>) -> Result<(), StatusCode> {
+    match slippage_bps {
+        Some(bps) if bps > pump::MAX_SLIPPAGE_BPS => {
+            warn!("Rejected slippage of {} bps", bps);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        _ => Ok(()),
+    }
+}
+
+// Mint secret keys must never be sent to the server; clients keeping their own mint use /tx/prepare-create
+fn reject_private_key(request: &CreateTokenRequest) -> Result<(), StatusCode> {
+    if request.pump_private_key.is_some() {
+        warn!("Rejected a create request carrying a mint private key from {}", request.wallet_address);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+// Launches are built here rather than by the SDK, whose create always names the server's
+// payer as creator: the requesting wallet is the creator and earns the token's creator fees.
+// Metadata is uploaded unless the client already did.
+async fn launch_args(request: &CreateTokenRequest, creator: Pubkey) -> Result<CreateArgs, String> {
+    let uri = match &request.metadata_uri {
+        Some(uri) => uri.clone(),
+        None => {
+            let metadata = CreateTokenMetadata {
+                name: request.name.clone(),
+                symbol: request.symbol.clone(),
+                description: request.description.clone(),
+                file: request.image_path.clone().unwrap_or_default(),
+                twitter: request.twitter.clone(),
+                telegram: request.telegram.clone(),
+                website: request.website.clone(),
+            };
+            pumpfun::utils::create_token_metadata(metadata)
+                .await
+                .map_err(|e| format!("metadata upload failed: {}", e))?
+                .metadata_uri
+        }
+    };
+    Ok(CreateArgs {
+        name: request.name.clone(),
+        symbol: request.symbol.clone(),
+        uri,
+        creator,
+    })
+}
+
+// The SDK only trades SPL Token mints on their curve; everything else goes through pump_trade,
+// including graduated tokens, which trade on PumpSwap under the same request and response
+async fn trade_route(state: &AppState, mint: &Pubkey) -> Result<Route, StatusCode> {
+    pump_trade::route(&state.rpc_client, mint).await.map_err(|e| {
+        warn!("Cannot trade {}: {}", mint, e);
+        trade_error_status(&e)
+    })
+}
+
+fn trade_error_status(error: &TradeError) -> StatusCode {
+    match error {
+        TradeError::MintNotFound(_) | TradeError::CurveNotFound(_) => StatusCode::NOT_FOUND,
+        TradeError::NotAMint { .. } | TradeError::NothingToSell => StatusCode::BAD_REQUEST,
+        TradeError::CurveComplete(_) | TradeError::PoolNotFound(_) => StatusCode::CONFLICT,
+        TradeError::Malformed(_) | TradeError::Rpc(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn submit_error_status(error: &SubmitError) -> StatusCode {
+    match error {
+        SubmitError::Malformed(_) | SubmitError::BadSignatures => StatusCode::BAD_REQUEST,
+        SubmitError::ForeignPayer(_) => StatusCode::FORBIDDEN,
+    }
+}
+
+// Take a keypair from the requested (or default) vanity pool, falling back to a random one
+async fn next_vanity_keypair(state: &AppState, pool: Option<&str>) -> Result<Keypair, StatusCode> {
+    let pool = pool.unwrap_or_else(|| state.vanity_service.default_pool_name());
+    match state.vanity_service.get_next_vanity_from(pool).await {
+        Ok(Some(keypair)) => Ok(keypair),
+        Ok(None) => {
+            warn!("No vanity keypairs available in pool {}, using random keypair", pool);
+            Ok(Keypair::new())
+        }
+        Err(e) => {
+            warn!("{}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
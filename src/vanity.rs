@@ -1,24 +1,66 @@
 use solana_sdk::{
+    pubkey::Pubkey,
     signature::Keypair,
     signer::Signer,
 };
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{info, warn};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
 use crossbeam_channel;
+use crate::metrics::metrics;
+use crate::vanity_file;
+use crate::vanity_grind::{GrindProgress, GrindReport, Grinder, PatternMatcher};
+use crate::vanity_ingest::{IngestError, IngestOutcome};
+use crate::vanity_pattern::VanityPattern;
 
-fn gen_keypair_fast() -> Keypair {
-    Keypair::new()
-}
-
-/// Vanity Address Service for generating Solana keypairs with custom suffixes
-pub struct VanityService {
+/// A single pool of pre-generated Solana keypairs sharing one vanity pattern
+pub struct VanityPool {
     pool: Arc<RwLock<Vec<Keypair>>>,
-    suffix: String,
+    pattern: VanityPattern,
     pool_size: usize,
+    refill: RefillConfig,
+    /// Batch file the pool was seeded from; remaining keypairs are written back on shutdown
+    file: Option<String>,
+    stop: Arc<AtomicBool>,
+    refill_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Counters for the refill batch being ground right now, if any
+    refill_progress: Arc<Mutex<Option<Arc<GrindProgress>>>>,
+    /// Addresses given out and not yet used or released, so a worker cannot feed one back
+    /// in meanwhile. A used address has its mint on-chain, where a second create fails anyway.
+    handed_out: Arc<Mutex<HashSet<Pubkey>>>,
+}
+
+/// Controls when the background grinder tops up the pool and how much CPU it may use
+#[derive(Clone, Copy, Debug)]
+pub struct RefillConfig {
+    /// Start grinding once the pool drops below this many keypairs
+    pub low_water_mark: usize,
+    /// Number of OS threads the grinder is allowed to occupy
+    pub threads: usize,
+}
+
+impl RefillConfig {
+    /// Refill as soon as the pool is below `pool_size`, leaving one core for the runtime
+    pub fn for_pool_size(pool_size: usize) -> Self {
+        Self {
+            low_water_mark: pool_size,
+            threads: default_grind_threads(),
+        }
+    }
+}
+
+/// Leave at least one core free so grinding does not starve the axum runtime
+pub fn default_grind_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get().saturating_sub(1))
+        .unwrap_or(1)
+        .max(1)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -35,51 +77,154 @@ pub struct VanityBatch {
     pub keypairs: Vec<VanityKeypair>,
 }
 
-impl VanityService {
-    /// Create a new VanityService with the specified suffix and pool size
-    pub fn new(suffix: String, pool_size: usize) -> Self {
-        let service = Self {
-            pool: Arc::new(RwLock::new(Vec::new())),
-            suffix,
-            pool_size,
-        };
-        
-        // Start background generation
-        let service_clone = service.clone();
-        tokio::spawn(async move {
-            service_clone.generate_pool().await;
-        });
-        
+impl VanityKeypair {
+    /// Decode the private key, checking that it really derives `public_key`
+    pub fn to_keypair(&self) -> Result<Keypair, String> {
+        let bytes = bs58::decode(&self.private_key)
+            .into_vec()
+            .map_err(|e| format!("private key is not base58: {}", e))?;
+        let keypair = Keypair::try_from(bytes.as_slice()).map_err(|e| format!("invalid private key: {}", e))?;
+        if keypair.pubkey().to_string() != self.public_key {
+            return Err(format!("private key derives {}, not {}", keypair.pubkey(), self.public_key));
+        }
+        Ok(keypair)
+    }
+}
+
+impl VanityBatch {
+    pub fn new(pattern: &VanityPattern, keypairs: Vec<VanityKeypair>) -> Self {
+        Self {
+            suffix: pattern.to_string(),
+            count: keypairs.len(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            keypairs,
+        }
+    }
+}
+
+impl From<&Keypair> for VanityKeypair {
+    fn from(kp: &Keypair) -> Self {
+        Self {
+            public_key: kp.pubkey().to_string(),
+            private_key: kp.to_base58_string(),
+        }
+    }
+}
+
+impl VanityPool {
+    /// Create a new pool with the specified pattern and pool size
+    pub fn new(pattern: VanityPattern, pool_size: usize) -> Self {
+        Self::with_refill(pattern, pool_size, RefillConfig::for_pool_size(pool_size))
+    }
+    
+    /// Create a new pool with an explicit refill policy
+    pub fn with_refill(pattern: VanityPattern, pool_size: usize, refill: RefillConfig) -> Self {
+        let service = Self::build(pattern, pool_size, refill, Vec::new(), None);
+        service.spawn_refill();
         service
     }
     
-    /// Create a VanityService with pre-loaded keypairs from a file
-    pub async fn from_file(file_path: &str, suffix: String, pool_size: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let file_content = std::fs::read_to_string(file_path)?;
-        let batch: VanityBatch = serde_json::from_str(&file_content)?;
+    /// Create a pool with pre-loaded keypairs from a file.
+    /// The file is the only source of keypairs; once it is exhausted callers get `None`.
+    pub async fn from_file(file_path: &str, pattern: VanityPattern, pool_size: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let keypairs = Self::load_keypairs(file_path, &pattern)?;
         
-        if batch.suffix != suffix {
-            return Err(format!("Suffix mismatch: expected '{}', got '{}'", suffix, batch.suffix).into());
+        // Don't start background generation when loading from file
+        // The pre-generated addresses should be sufficient
+        let refill = RefillConfig::for_pool_size(pool_size);
+        Ok(Self::build(pattern, pool_size, refill, keypairs, Some(file_path.to_string())))
+    }
+    
+    /// Create a pool seeded from a file that keeps grinding in the background
+    /// whenever the pool falls below `refill.low_water_mark`
+    pub async fn from_file_with_refill(
+        file_path: &str,
+        pattern: VanityPattern,
+        pool_size: usize,
+        refill: RefillConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let keypairs = Self::load_keypairs(file_path, &pattern)?;
+        let service = Self::build(pattern, pool_size, refill, keypairs, Some(file_path.to_string()));
+        service.spawn_refill();
+        Ok(service)
+    }
+    
+    /// Pool holding `keypairs`, with no refill task running yet
+    fn build(
+        pattern: VanityPattern,
+        pool_size: usize,
+        refill: RefillConfig,
+        keypairs: Vec<Keypair>,
+        file: Option<String>,
+    ) -> Self {
+        Self {
+            pool: Arc::new(RwLock::new(keypairs)),
+            pattern,
+            pool_size,
+            refill,
+            file,
+            stop: Arc::new(AtomicBool::new(false)),
+            refill_task: Arc::new(Mutex::new(None)),
+            refill_progress: Arc::new(Mutex::new(None)),
+            handed_out: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+    
+    fn load_keypairs(file_path: &str, pattern: &VanityPattern) -> Result<Vec<Keypair>, Box<dyn std::error::Error>> {
+        // Grinding logs with one batch per line load as well as flushed pools
+        let batches = vanity_file::read_batches(Path::new(file_path))?;
+        
+        let expected = pattern.to_string();
+        if let Some(batch) = batches.iter().find(|batch| batch.suffix != expected) {
+            return Err(format!("Pattern mismatch: expected '{}', got '{}'", expected, batch.suffix).into());
         }
         
         let mut keypairs = Vec::new();
-        for vanity_kp in batch.keypairs {
+        for vanity_kp in batches.into_iter().flat_map(|batch| batch.keypairs) {
             let private_key_bytes = bs58::decode(&vanity_kp.private_key).into_vec()?;
             let keypair = Keypair::try_from(private_key_bytes.as_slice())?;
+            if !pattern.matches(&keypair.pubkey().to_string()) {
+                return Err(format!("Address {} does not match pattern '{}'", keypair.pubkey(), expected).into());
+            }
             keypairs.push(keypair);
         }
         
         info!("Loaded {} pre-generated vanity addresses from {}", keypairs.len(), file_path);
-        
-        let service = Self {
-            pool: Arc::new(RwLock::new(keypairs)),
-            suffix,
-            pool_size,
+        Ok(keypairs)
+    }
+    
+    fn spawn_refill(&self) {
+        let service_clone = self.clone();
+        let handle = tokio::spawn(async move {
+            service_clone.generate_pool().await;
+        });
+        *self.refill_task.lock().unwrap() = Some(handle);
+    }
+    
+    /// Stop the background grinder, keeping whatever it found in the current batch.
+    /// Waits for the grinding threads to exit.
+    pub async fn stop_refill(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let handle = self.refill_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if handle.await.is_err() {
+                warn!("Vanity refill task for pattern {} panicked", self.pattern);
+            }
+        }
+    }
+    
+    /// Write the unused keypairs back to the pool's batch file so the next start
+    /// neither loses them nor hands out ones that were already used.
+    /// Returns `None` for pools that were not seeded from a file.
+    pub async fn flush(&self) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        let Some(file_path) = &self.file else {
+            return Ok(None);
         };
         
-        // Don't start background generation when loading from file
-        // The pre-generated addresses should be sufficient
-        Ok(service)
+        let pool = self.pool.read().await;
+        let batch = VanityBatch::new(&self.pattern, pool.iter().map(VanityKeypair::from).collect());
+        vanity_file::write_atomic(Path::new(file_path), &batch)?;
+        Ok(Some(batch.count))
     }
     
     /// Get the next vanity keypair from the pool
@@ -87,6 +232,7 @@ impl VanityService {
         let mut pool = self.pool.write().await;
         
         if let Some(keypair) = pool.pop() {
+            self.handed_out.lock().unwrap().insert(keypair.pubkey());
             let pubkey_str = keypair.pubkey().to_string();
             info!("Using vanity address: {}", pubkey_str);
             return Some(keypair);
@@ -96,26 +242,93 @@ impl VanityService {
         None
     }
     
+    /// Put back a keypair that was handed out but never used on-chain
+    pub async fn release(&self, keypair: Keypair) {
+        self.handed_out.lock().unwrap().remove(&keypair.pubkey());
+        self.pool.write().await.push(keypair);
+    }
+    
+    /// Stop tracking a handed-out address that will not come back to the pool.
+    /// Returns whether this pool had handed it out.
+    pub fn mark_used(&self, pubkey: &Pubkey) -> bool {
+        self.handed_out.lock().unwrap().remove(pubkey)
+    }
+    
+    /// Add keypairs ground elsewhere. Each one must derive its address and match the
+    /// pool's pattern; ones already pooled or handed out are dropped, and nothing is
+    /// added past the pool's target size.
+    pub async fn ingest(&self, keypairs: Vec<VanityKeypair>) -> IngestOutcome {
+        let mut outcome = IngestOutcome { target_size: self.pool_size, ..IngestOutcome::default() };
+        let mut verified = Vec::with_capacity(keypairs.len());
+        for vanity_kp in keypairs {
+            match vanity_kp.to_keypair() {
+                Ok(keypair) if self.pattern.matches(&vanity_kp.public_key) => verified.push(keypair),
+                _ => outcome.invalid += 1,
+            }
+        }
+        
+        let mut pool = self.pool.write().await;
+        let handed_out = self.handed_out.lock().unwrap();
+        let mut known: HashSet<Pubkey> = pool.iter().map(|keypair| keypair.pubkey()).collect();
+        for keypair in verified {
+            if handed_out.contains(&keypair.pubkey()) || !known.insert(keypair.pubkey()) {
+                outcome.duplicates += 1;
+            } else if pool.len() >= self.pool_size {
+                outcome.surplus += 1;
+            } else {
+                pool.push(keypair);
+                outcome.accepted += 1;
+            }
+        }
+        outcome.pool_size = pool.len();
+        outcome
+    }
     
     /// Get current pool size
     pub async fn pool_size(&self) -> usize {
         self.pool.read().await.len()
     }
     
-    /// Generate a single vanity keypair with the specified suffix
+    /// Pattern every keypair in this pool matches
+    pub fn pattern(&self) -> &VanityPattern {
+        &self.pattern
+    }
+    
+    /// Snapshot of the pool's fill level and refill policy
+    pub async fn stats(&self, name: &str) -> PoolStats {
+        PoolStats {
+            name: name.to_string(),
+            pattern: self.pattern.to_string(),
+            expected_attempts: self.pattern.expected_attempts(),
+            pool_size: self.pool_size().await,
+            target_size: self.pool_size,
+            low_water_mark: self.refill.low_water_mark,
+            refill: self
+                .refill_progress
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|progress| progress.report(self.pattern.expected_attempts())),
+        }
+    }
+    
+    /// Generate a single vanity keypair matching the specified pattern
     /// Fast parallel implementation that stops as soon as one thread finds a match
-    pub fn generate_single_vanity(suffix: &str) -> Keypair {
+    pub fn generate_single_vanity(pattern: &VanityPattern) -> Keypair {
         let found = AtomicBool::new(false);
-        let batch_per_thread = 10_000;
+        let batches_per_thread = 3;
+        let matcher = PatternMatcher::new(pattern);
 
         loop {
             let maybe = (0..rayon::current_num_threads())
                 .into_par_iter()
                 .find_map_any(|_| {
-                    for _ in 0..batch_per_thread {
+                    let mut grinder = Grinder::new(&matcher);
+                    let mut hits = Vec::new();
+                    for _ in 0..batches_per_thread {
                         if found.load(Ordering::Relaxed) { return None; }
-                        let kp = gen_keypair_fast();
-                        if kp.pubkey().to_string().ends_with(suffix) {
+                        grinder.next_batch(&mut hits);
+                        if let Some(kp) = hits.pop() {
                             found.store(true, Ordering::Relaxed);
                             return Some(kp);
                         }
@@ -131,46 +344,94 @@ impl VanityService {
     }
     
     /// Generate multiple vanity keypairs in parallel efficiently
-    pub fn generate_vanity_batch(suffix: &str, count: usize) -> Vec<Keypair> {
+    pub fn generate_vanity_batch(pattern: &VanityPattern, count: usize) -> Vec<Keypair> {
+        Self::generate_vanity_batch_with_threads(pattern, count, rayon::current_num_threads().max(2))
+    }
+    
+    /// Generate multiple vanity keypairs using at most `threads` worker threads
+    pub fn generate_vanity_batch_with_threads(pattern: &VanityPattern, count: usize, threads: usize) -> Vec<Keypair> {
+        Self::grind_batch(pattern, count, threads, &AtomicBool::new(false), &GrindProgress::new(count as u64))
+    }
+    
+    /// Like [`VanityPool::generate_vanity_batch_with_threads`], but returns early with
+    /// fewer keypairs once `cancel` is set
+    fn grind_batch(
+        pattern: &VanityPattern,
+        count: usize,
+        threads: usize,
+        cancel: &AtomicBool,
+        progress: &GrindProgress,
+    ) -> Vec<Keypair> {
+        let mut out = Vec::with_capacity(count);
+        if count > 0 {
+            Self::grind_each(pattern, threads, cancel, progress, |kp| {
+                out.push(kp);
+                out.len() < count
+            });
+        }
+        out
+    }
+    
+    /// Grind on `threads` worker threads, handing each match to `on_hit` on the calling
+    /// thread until it returns `false` or `cancel` is set. Workers count into `progress`
+    /// as they go, so another thread can report on the grind while it runs.
+    pub fn grind_each(
+        pattern: &VanityPattern,
+        threads: usize,
+        cancel: &AtomicBool,
+        progress: &GrindProgress,
+        mut on_hit: impl FnMut(Keypair) -> bool,
+    ) {
         let (tx, rx) = crossbeam_channel::unbounded();
         let stop = AtomicBool::new(false);
-        let workers = rayon::current_num_threads().max(2);
+        let workers = threads.max(1);
+        let matcher = PatternMatcher::new(pattern);
+        let label = pattern.to_string();
+        let attempted = metrics().vanity_keys_attempted.with_label_values(&[&label]);
+        let found = metrics().vanity_keys_found.with_label_values(&[&label]);
 
         std::thread::scope(|scope| {
             for _ in 0..workers {
                 let tx = tx.clone();
                 let stop = &stop;
+                let matcher = &matcher;
+                let (attempted, found) = (attempted.clone(), found.clone());
                 scope.spawn(move || {
-                    while !stop.load(Ordering::Relaxed) {
-                        for _ in 0..5_000 {
-                            if stop.load(Ordering::Relaxed) { break; }
-                            let kp = gen_keypair_fast();
-                            if kp.pubkey().to_string().ends_with(suffix) {
-                                if tx.send(kp).is_err() { return; }
-                            }
+                    let mut grinder = Grinder::new(matcher);
+                    let mut hits = Vec::new();
+                    while !stop.load(Ordering::Relaxed) && !cancel.load(Ordering::Relaxed) {
+                        let tried = grinder.next_batch(&mut hits);
+                        progress.record(tried, hits.len());
+                        attempted.inc_by(tried as u64);
+                        found.inc_by(hits.len() as u64);
+                        for kp in hits.drain(..) {
+                            if tx.send(kp).is_err() { return; }
                         }
                     }
                 });
             }
 
-            let mut out = Vec::with_capacity(count);
-            for _ in 0..count {
-                if let Ok(kp) = rx.recv() {
-                    out.push(kp);
+            // Only the workers hold senders now, so `recv` fails once they all stop
+            drop(tx);
+            while let Ok(kp) = rx.recv() {
+                if !on_hit(kp) {
+                    break;
                 }
             }
             stop.store(true, Ordering::Relaxed);
-            out
         })
     }
     
     /// Background task to maintain the vanity pool
     async fn generate_pool(&self) {
-        info!("Starting vanity address generation for suffix: {}", self.suffix);
+        info!(
+            "Starting vanity address generation for pattern: {} (low-water mark {}, {} threads)",
+            self.pattern, self.refill.low_water_mark, self.refill.threads
+        );
         
-        loop {
+        while !self.stop.load(Ordering::Relaxed) {
             let current_size = self.pool.read().await.len();
-            if current_size >= self.pool_size {
+            if current_size >= self.refill.low_water_mark.min(self.pool_size) {
                 tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 continue;
             }
@@ -179,29 +440,258 @@ impl VanityService {
             info!("Generating {} vanity addresses...", needed);
             
             // Use a proper thread pool for CPU-intensive work
-            let suffix = self.suffix.clone();
+            let pattern = self.pattern.clone();
+            let threads = self.refill.threads;
+            let stop = self.stop.clone();
+            let progress = Arc::new(GrindProgress::new(needed as u64));
+            *self.refill_progress.lock().unwrap() = Some(progress.clone());
             let new_keypairs = tokio::task::spawn_blocking(move || {
-                Self::generate_vanity_batch(&suffix, needed)
+                Self::grind_batch(&pattern, needed, threads, &stop, &progress)
             }).await.unwrap_or_else(|_| {
                 warn!("Vanity generation task failed");
                 Vec::new()
             });
+            let finished = self.refill_progress.lock().unwrap().take();
+            let rate = finished.map_or(0.0, |progress| progress.report(self.pattern.expected_attempts()).attempts_per_sec);
             
             {
                 let mut pool = self.pool.write().await;
                 pool.extend(new_keypairs);
-                info!("Vanity pool size: {}", pool.len());
+                info!("Vanity pool size: {} (ground at {:.0} keys/sec)", pool.len(), rate);
             }
         }
+        
+        info!("Stopped vanity address generation for pattern: {}", self.pattern);
     }
 }
 
-impl Clone for VanityService {
+/// Configuration for one named pool managed by [`VanityService`]
+#[derive(Clone, Debug)]
+pub struct PoolSpec {
+    pub name: String,
+    pub pattern: VanityPattern,
+    pub pool_size: usize,
+    /// Optional pre-generated batch file used to seed the pool
+    pub file: Option<String>,
+}
+
+impl PoolSpec {
+    /// Parse a comma-separated list of `name:pattern:size[:file]` entries,
+    /// e.g. `pump:pump:120,ony:ony*:50:ony.json`. Patterns use [`VanityPattern::parse`] syntax.
+    pub fn parse_list(spec: &str) -> Result<Vec<PoolSpec>, String> {
+        let mut specs: Vec<PoolSpec> = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').collect();
+            if parts.len() < 3 || parts.len() > 4 {
+                return Err(format!("Invalid vanity pool '{}': expected name:pattern:size[:file]", entry));
+            }
+            let pool_size = parts[2]
+                .parse()
+                .map_err(|_| format!("Invalid pool size '{}' for vanity pool '{}'", parts[2], parts[0]))?;
+            let pattern = VanityPattern::parse(parts[1])
+                .map_err(|e| format!("Invalid pattern for vanity pool '{}': {}", parts[0], e))?;
+            if specs.iter().any(|s| s.name == parts[0]) {
+                return Err(format!("Duplicate vanity pool name '{}'", parts[0]));
+            }
+            specs.push(PoolSpec {
+                name: parts[0].to_string(),
+                pattern,
+                pool_size,
+                file: parts.get(3).map(|f| f.to_string()),
+            });
+        }
+        if specs.is_empty() {
+            return Err("No vanity pools configured".to_string());
+        }
+        Ok(specs)
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PoolStats {
+    pub name: String,
+    pub pattern: String,
+    pub expected_attempts: f64,
+    pub pool_size: usize,
+    pub target_size: usize,
+    pub low_water_mark: usize,
+    /// Progress of the background refill, while one is running
+    pub refill: Option<GrindReport>,
+}
+
+/// Vanity Address Service managing several named pools of Solana keypairs
+#[derive(Clone)]
+pub struct VanityService {
+    pools: Arc<BTreeMap<String, VanityPool>>,
+    default_pool: String,
+}
+
+impl VanityService {
+    /// Create a service with a single generated pool named after its pattern
+    pub fn new(pattern: VanityPattern, pool_size: usize) -> Self {
+        let name = pattern.to_string();
+        let mut pools = BTreeMap::new();
+        pools.insert(name.clone(), VanityPool::new(pattern, pool_size));
+        Self {
+            pools: Arc::new(pools),
+            default_pool: name,
+        }
+    }
+    
+    /// Build every configured pool, seeding from its file when one is given.
+    /// `grind_threads` is the total grinding budget and is split across pools;
+    /// without an explicit `low_water_mark` each pool refills below a quarter of its target
+    /// (at least one keypair, so small pools still refill).
+    pub async fn from_specs(
+        specs: Vec<PoolSpec>,
+        default_pool: Option<String>,
+        low_water_mark: Option<usize>,
+        grind_threads: usize,
+        refill_from_file: bool,
+    ) -> Result<Self, String> {
+        let default_pool = default_pool
+            .or_else(|| specs.first().map(|s| s.name.clone()))
+            .ok_or_else(|| "No vanity pools configured".to_string())?;
+        if !specs.iter().any(|s| s.name == default_pool) {
+            return Err(format!("Default vanity pool '{}' is not configured", default_pool));
+        }
+        
+        let threads_per_pool = (grind_threads / specs.len().max(1)).max(1);
+        let mut pools = BTreeMap::new();
+        for spec in specs {
+            let pool_refill = RefillConfig {
+                low_water_mark: low_water_mark.unwrap_or(spec.pool_size.div_ceil(4).max(1)).min(spec.pool_size),
+                threads: threads_per_pool,
+            };
+            let pool = match &spec.file {
+                Some(file) => {
+                    let loaded = if refill_from_file {
+                        VanityPool::from_file_with_refill(file, spec.pattern.clone(), spec.pool_size, pool_refill).await
+                    } else {
+                        VanityPool::from_file(file, spec.pattern.clone(), spec.pool_size).await
+                    };
+                    match loaded {
+                        Ok(pool) => pool,
+                        Err(e) => {
+                            warn!("Failed to load vanity file {} for pool {}: {}. Falling back to generation.", file, spec.name, e);
+                            VanityPool::with_refill(spec.pattern.clone(), spec.pool_size, pool_refill)
+                        }
+                    }
+                }
+                None => VanityPool::with_refill(spec.pattern.clone(), spec.pool_size, pool_refill),
+            };
+            info!(
+                "Vanity pool '{}' ready (pattern: {}, target: {}, ~{:.0} attempts per hit)",
+                spec.name, spec.pattern, spec.pool_size, spec.pattern.expected_attempts()
+            );
+            pools.insert(spec.name, pool);
+        }
+        
+        Ok(Self {
+            pools: Arc::new(pools),
+            default_pool,
+        })
+    }
+    
+    /// Name of the pool used when a request does not pick one
+    pub fn default_pool_name(&self) -> &str {
+        &self.default_pool
+    }
+    
+    /// Look up a pool by name
+    pub fn pool(&self, name: &str) -> Option<&VanityPool> {
+        self.pools.get(name)
+    }
+    
+    /// Get the next vanity keypair from the default pool
+    pub async fn get_next_vanity(&self) -> Option<Keypair> {
+        self.get_next_vanity_from(&self.default_pool).await.ok().flatten()
+    }
+    
+    /// Get the next vanity keypair from the named pool.
+    /// Returns an error if no pool with that name exists.
+    pub async fn get_next_vanity_from(&self, name: &str) -> Result<Option<Keypair>, String> {
+        match self.pools.get(name) {
+            Some(pool) => Ok(pool.get_next_vanity().await),
+            None => Err(format!("Unknown vanity pool '{}'", name)),
+        }
+    }
+    
+    /// Add keypairs pushed by a remote worker to the named pool
+    pub async fn ingest(&self, name: &str, keypairs: Vec<VanityKeypair>) -> Result<IngestOutcome, IngestError> {
+        match self.pools.get(name) {
+            Some(pool) => Ok(pool.ingest(keypairs).await),
+            None => Err(IngestError::UnknownPool(name.to_string())),
+        }
+    }
+    
+    /// Forget a handed-out address once its create has finished, or failed and dropped the
+    /// keypair. Addresses from outside the pools are ignored.
+    pub fn mark_used(&self, pubkey: &Pubkey) {
+        for pool in self.pools.values() {
+            if pool.mark_used(pubkey) {
+                return;
+            }
+        }
+    }
+    
+    /// Put back an unused keypair taken from the named pool; dropped if the pool is gone
+    pub async fn release(&self, name: &str, keypair: Keypair) {
+        match self.pools.get(name) {
+            Some(pool) => pool.release(keypair).await,
+            None => warn!("Dropping vanity keypair {} for unknown pool '{}'", keypair.pubkey(), name),
+        }
+    }
+    
+    /// Current size of the default pool
+    pub async fn pool_size(&self) -> usize {
+        match self.pools.get(&self.default_pool) {
+            Some(pool) => pool.pool_size().await,
+            None => 0,
+        }
+    }
+    
+    /// Stop every pool's grinder, then write file-backed pools back to disk
+    pub async fn shutdown(&self) {
+        for pool in self.pools.values() {
+            pool.stop.store(true, Ordering::Relaxed);
+        }
+        for (name, pool) in self.pools.iter() {
+            pool.stop_refill().await;
+            match pool.flush().await {
+                Ok(Some(count)) => info!("Saved {} unused vanity keypairs from pool '{}'", count, name),
+                Ok(None) => info!(
+                    "Vanity pool '{}' has no file; {} unused keypairs are discarded",
+                    name,
+                    pool.pool_size().await
+                ),
+                Err(e) => warn!("Failed to save vanity pool '{}': {}", name, e),
+            }
+        }
+    }
+    
+    /// Per-pool statistics, ordered by pool name
+    pub async fn stats(&self) -> Vec<PoolStats> {
+        let mut stats = Vec::with_capacity(self.pools.len());
+        for (name, pool) in self.pools.iter() {
+            stats.push(pool.stats(name).await);
+        }
+        stats
+    }
+}
+
+impl Clone for VanityPool {
     fn clone(&self) -> Self {
         Self {
             pool: self.pool.clone(),
-            suffix: self.suffix.clone(),
+            pattern: self.pattern.clone(),
             pool_size: self.pool_size,
+            refill: self.refill,
+            file: self.file.clone(),
+            stop: self.stop.clone(),
+            refill_task: self.refill_task.clone(),
+            refill_progress: self.refill_progress.clone(),
+            handed_out: self.handed_out.clone(),
         }
     }
 }
@@ -213,7 +703,7 @@ mod tests {
     #[test]
     fn test_generate_single_vanity_short_suffix() {
         let suffix = "pump"; // Very fast - 1 in 58 chance
-        let keypair = VanityService::generate_single_vanity(suffix);
+        let keypair = VanityPool::generate_single_vanity(&VanityPattern::parse(suffix).unwrap());
         let public_key = keypair.pubkey().to_string();
         
         println!("Generated vanity address: {}", public_key);
@@ -225,7 +715,7 @@ mod tests {
     #[test]
     fn test_generate_single_vanity_long_suffix() {
         let suffix = "pump"; // ~1 in 58^4 chance
-        let keypair = VanityService::generate_single_vanity(suffix);
+        let keypair = VanityPool::generate_single_vanity(&VanityPattern::parse(suffix).unwrap());
         let public_key = keypair.pubkey().to_string();
         
         println!("Generated vanity address: {}", public_key);
@@ -237,7 +727,7 @@ mod tests {
     fn test_generate_vanity_batch() {
         let suffix = "a"; // Fast suffix for testing
         let count = 3;
-        let keypairs = VanityService::generate_vanity_batch(suffix, count);
+        let keypairs = VanityPool::generate_vanity_batch(&VanityPattern::parse(suffix).unwrap(), count);
         
         assert_eq!(keypairs.len(), count);
         
@@ -249,16 +739,84 @@ mod tests {
         }
     }
 
+    // The constructor spawns the refill task, so it needs a runtime
+    #[tokio::test]
+    async fn test_vanity_service_creation() {
+        let service = VanityService::new(VanityPattern::parse("test").unwrap(), 5);
+        let pool = service.pool("test").expect("default pool should exist");
+        assert_eq!(pool.pattern.to_string(), "test");
+        assert_eq!(pool.pool_size, 5);
+        assert_eq!(service.default_pool_name(), "test");
+    }
+
+    #[tokio::test]
+    async fn test_small_file_pool_refills_once_drained() {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let path = std::env::temp_dir().join(format!("onlypump-refill-{}.json", Keypair::new().pubkey()));
+        let seed = VanityPool::generate_vanity_batch(&pattern, 3);
+        let batch = VanityBatch::new(&pattern, seed.iter().map(VanityKeypair::from).collect());
+        std::fs::write(&path, serde_json::to_string(&batch).unwrap()).unwrap();
+        let spec = PoolSpec {
+            name: "a".to_string(),
+            pattern,
+            pool_size: 3,
+            file: Some(path.to_str().unwrap().to_string()),
+        };
+        let service = VanityService::from_specs(vec![spec], None, None, 1, true).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let pool = service.pool("a").unwrap();
+        // A quarter of 3 rounds up rather than down to a mark that is never undercut
+        assert_eq!(pool.refill.low_water_mark, 1);
+
+        for _ in 0..3 {
+            pool.get_next_vanity().await.unwrap();
+        }
+        let refilled = tokio::time::timeout(std::time::Duration::from_secs(30), async {
+            while pool.pool_size().await < 3 {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await;
+        assert!(refilled.is_ok(), "drained pool was not refilled");
+        pool.stop_refill().await;
+    }
+
     #[test]
-    fn test_vanity_service_creation() {
-        let service = VanityService::new("test".to_string(), 5);
-        assert_eq!(service.suffix, "test");
-        assert_eq!(service.pool_size, 5);
+    fn test_pool_spec_parse_list() {
+        let specs = PoolSpec::parse_list("pump:pump:120, ony:ony*:50:ony.json").unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].name, "pump");
+        assert_eq!(specs[0].pool_size, 120);
+        assert_eq!(specs[0].file, None);
+        assert_eq!(specs[1].pattern, VanityPattern::prefix("ony").unwrap());
+        assert_eq!(specs[1].file.as_deref(), Some("ony.json"));
+
+        assert!(PoolSpec::parse_list("pump:pump").is_err());
+        assert!(PoolSpec::parse_list("pump:pump:many").is_err());
+        assert!(PoolSpec::parse_list("a:a:1,a:b:2").is_err());
+        assert!(PoolSpec::parse_list("only:only:10").is_err(), "'l' is not base58");
+        assert!(PoolSpec::parse_list("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vanity_service_named_pools() {
+        let specs = PoolSpec::parse_list("first:a:1,second:b:1").unwrap();
+        let service = VanityService::from_specs(specs, Some("second".to_string()), Some(1), 2, true).await.unwrap();
+
+        assert_eq!(service.default_pool_name(), "second");
+        assert!(service.get_next_vanity_from("missing").await.is_err());
+
+        let stats = service.stats().await;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].name, "first");
+        assert_eq!(stats[1].pattern, "b");
+        assert_eq!(stats[1].expected_attempts, 58.0);
+        assert!(VanityService::from_specs(PoolSpec::parse_list("a:a:1").unwrap(), Some("zzz".to_string()), None, 1, true).await.is_err());
     }
 
     #[tokio::test]
     async fn test_vanity_service_pool_operations() {
-        let service = VanityService::new("xyz".to_string(), 2);
+        let service = VanityService::new(VanityPattern::parse("xyz").unwrap(), 2);
         
         // Wait a bit for background generation
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -278,7 +836,7 @@ mod tests {
     fn test_vanity_generation_performance() {
         let suffix = "a";
         let start = std::time::Instant::now();
-        let keypair = VanityService::generate_single_vanity(suffix);
+        let keypair = VanityPool::generate_single_vanity(&VanityPattern::parse(suffix).unwrap());
         let duration = start.elapsed();
         
         let public_key = keypair.pubkey().to_string();
@@ -287,4 +845,117 @@ mod tests {
         println!("Generated vanity address '{}' in {:?}", public_key, duration);
         assert!(duration.as_secs() < 10, "Vanity generation should complete within 10 seconds");
     }
+
+    #[tokio::test]
+    async fn test_stop_refill_and_flush_round_trip() {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let path = std::env::temp_dir().join(format!("onlypump-vanity-{}.json", Keypair::new().pubkey()));
+        let path = path.to_str().unwrap().to_string();
+        let seed = VanityPool::generate_vanity_batch(&pattern, 2);
+        let batch = VanityBatch::new(&pattern, seed.iter().map(VanityKeypair::from).collect());
+        std::fs::write(&path, serde_json::to_string(&batch).unwrap()).unwrap();
+
+        // A target this large keeps the grinder busy until it is told to stop
+        let refill = RefillConfig { low_water_mark: 100_000, threads: 1 };
+        let pool = VanityPool::from_file_with_refill(&path, pattern.clone(), 100_000, refill).await.unwrap();
+        let used = pool.get_next_vanity().await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(10), pool.stop_refill())
+            .await
+            .expect("grinder stops promptly");
+
+        let saved = pool.flush().await.unwrap().expect("file-backed pool is flushed");
+        assert_eq!(saved, pool.pool_size().await);
+        let reloaded = VanityPool::load_keypairs(&path, &pattern).unwrap();
+        assert_eq!(reloaded.len(), saved);
+        assert!(reloaded.iter().all(|kp| kp.pubkey() != used.pubkey()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stats_report_refill_progress() {
+        // Practically unreachable, so the refill is still running when stats are taken
+        let pattern = VanityPattern::parse("zzzzzzzz").unwrap();
+        let pool = VanityPool::with_refill(pattern, 3, RefillConfig { low_water_mark: 3, threads: 1 });
+
+        let mut refill = None;
+        for _ in 0..100 {
+            refill = pool.stats("slow").await.refill.filter(|r| r.attempts > 0);
+            if refill.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        let refill = refill.expect("refill progress is reported");
+        assert_eq!(refill.target, 3);
+        assert!(refill.attempts_per_sec > 0.0);
+        assert!(refill.eta_secs.unwrap() > 0.0);
+
+        pool.stop_refill().await;
+        assert!(pool.stats("slow").await.refill.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_verifies_and_dedupes() {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let path = std::env::temp_dir().join(format!("onlypump-ingest-{}.json", Keypair::new().pubkey()));
+        let seed = VanityPool::generate_vanity_batch(&pattern, 2);
+        let batch = VanityBatch::new(&pattern, seed.iter().map(VanityKeypair::from).collect());
+        std::fs::write(&path, serde_json::to_string(&batch).unwrap()).unwrap();
+        let pool = VanityPool::from_file(path.to_str().unwrap(), pattern.clone(), 4).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Hands out seed[1]
+        pool.get_next_vanity().await.unwrap();
+
+        let fresh = VanityPool::generate_vanity_batch(&pattern, 4);
+        let off_pattern = std::iter::repeat_with(Keypair::new)
+            .find(|kp| !pattern.matches(&kp.pubkey().to_string()))
+            .unwrap();
+        let mut incoming: Vec<VanityKeypair> = [&seed[0], &seed[1], &off_pattern]
+            .into_iter()
+            .chain(&fresh)
+            .map(VanityKeypair::from)
+            .collect();
+        incoming.push(VanityKeypair::from(&fresh[0]));
+
+        let outcome = pool.ingest(incoming).await;
+        // One seed key is still pooled, so three fresh ones fit and the fourth is surplus
+        let expected = IngestOutcome { accepted: 3, duplicates: 3, invalid: 1, surplus: 1, pool_size: 4, target_size: 4 };
+        assert_eq!(outcome, expected);
+        assert_eq!(pool.pool_size().await, 4);
+    }
+
+    #[tokio::test]
+    async fn test_handed_out_addresses_are_forgotten_once_settled() {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let path = std::env::temp_dir().join(format!("onlypump-settle-{}.json", Keypair::new().pubkey()));
+        let seed = VanityPool::generate_vanity_batch(&pattern, 3);
+        let batch = VanityBatch::new(&pattern, seed.iter().map(VanityKeypair::from).collect());
+        std::fs::write(&path, serde_json::to_string(&batch).unwrap()).unwrap();
+        let pool = VanityPool::from_file(path.to_str().unwrap(), pattern, 3).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let used = pool.get_next_vanity().await.unwrap();
+        let released = pool.get_next_vanity().await.unwrap();
+        assert_eq!(pool.handed_out.lock().unwrap().len(), 2);
+
+        pool.release(released).await;
+        assert!(pool.mark_used(&used.pubkey()));
+        assert!(!pool.mark_used(&Keypair::new().pubkey()));
+        assert!(pool.handed_out.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_to_keypair_checks_public_key() {
+        let keypairs = [Keypair::new(), Keypair::new()];
+        let stored = VanityKeypair::from(&keypairs[1]);
+        assert_eq!(stored.to_keypair().unwrap().pubkey(), keypairs[1].pubkey());
+
+        let forged = VanityKeypair {
+            public_key: keypairs[0].pubkey().to_string(),
+            private_key: stored.private_key.clone(),
+        };
+        assert!(forged.to_keypair().unwrap_err().contains("derives"));
+        let garbled = VanityKeypair { private_key: "0OIl".to_string(), ..stored };
+        assert!(garbled.to_keypair().is_err());
+    }
 }
@@ -0,0 +1,209 @@
+//! Vanity batch files on disk.
+//!
+//! A file holds one or more [`VanityBatch`]es as concatenated JSON: a single pretty
+//! batch (written by [`write_atomic`]) or a log with one batch per line (appended by
+//! [`append`] while grinding). Files from older `generate_pump` runs, with batches
+//! separated by `---` lines, still read.
+
+use crate::vanity::VanityBatch;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Separator the first `generate_pump` wrote between pretty-printed batches
+const LEGACY_SEPARATOR: &str = "\n---";
+
+#[derive(Debug, Error)]
+pub enum VanityFileError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}: {source}", path.display())]
+    Parse { path: PathBuf, source: serde_json::Error },
+}
+
+impl VanityFileError {
+    fn io(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Io { path: path.to_path_buf(), source }
+    }
+}
+
+pub fn read_batches(path: &Path) -> Result<Vec<VanityBatch>, VanityFileError> {
+    let content = std::fs::read_to_string(path).map_err(VanityFileError::io(path))?;
+    parse_batches(&content).map_err(|source| VanityFileError::Parse { path: path.to_path_buf(), source })
+}
+
+pub fn parse_batches(content: &str) -> Result<Vec<VanityBatch>, serde_json::Error> {
+    let mut batches = Vec::new();
+    for chunk in content.split(LEGACY_SEPARATOR) {
+        for batch in serde_json::Deserializer::from_str(chunk).into_iter::<VanityBatch>() {
+            batches.push(batch?);
+        }
+    }
+    Ok(batches)
+}
+
+/// Replace `path` with `batch`: write a synced temp file next to it and rename it into
+/// place, so readers and crashes only ever see the old or the new contents
+pub fn write_atomic(path: &Path, batch: &VanityBatch) -> Result<(), VanityFileError> {
+    let json = serde_json::to_vec_pretty(batch).map_err(|source| VanityFileError::Parse { path: path.to_path_buf(), source })?;
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = File::create(&tmp_path).map_err(VanityFileError::io(&tmp_path))?;
+    file.write_all(&json)
+        .and_then(|()| file.sync_all())
+        .map_err(VanityFileError::io(&tmp_path))?;
+    std::fs::rename(&tmp_path, path).map_err(VanityFileError::io(path))?;
+    sync_parent(path)
+}
+
+/// Append `batch` as one line and sync it, so a crash loses at most the line being written
+pub fn append(path: &Path, batch: &VanityBatch) -> Result<(), VanityFileError> {
+    let mut line = serde_json::to_vec(batch).map_err(|source| VanityFileError::Parse { path: path.to_path_buf(), source })?;
+    line.push(b'\n');
+
+    let created = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(VanityFileError::io(path))?;
+    file.write_all(&line)
+        .and_then(|()| file.sync_data())
+        .map_err(VanityFileError::io(path))?;
+    if created {
+        sync_parent(path)?;
+    }
+    Ok(())
+}
+
+/// Cut off a batch that was only partly written when a previous run died.
+/// Returns how many bytes were dropped; corruption anywhere else is an error,
+/// since those bytes may hold keys that are still needed.
+pub fn repair_tail(path: &Path) -> Result<u64, VanityFileError> {
+    let content = std::fs::read_to_string(path).map_err(VanityFileError::io(path))?;
+    let complete = complete_len(&content).map_err(|source| VanityFileError::Parse { path: path.to_path_buf(), source })?;
+    if complete == content.len() {
+        return Ok(0);
+    }
+
+    let mut file = OpenOptions::new().append(true).open(path).map_err(VanityFileError::io(path))?;
+    file.set_len(complete as u64).map_err(VanityFileError::io(path))?;
+    // Keep one batch per line for whatever gets appended next
+    let kept = &content[..complete];
+    if !kept.is_empty() && !kept.ends_with('\n') {
+        file.write_all(b"\n").map_err(VanityFileError::io(path))?;
+    }
+    file.sync_all().map_err(VanityFileError::io(path))?;
+    Ok((content.len() - complete) as u64)
+}
+
+/// Length of `content` up to the end of its last complete batch
+fn complete_len(content: &str) -> Result<usize, serde_json::Error> {
+    // Everything before the last legacy separator was written by a finished run
+    let start = content
+        .rfind(LEGACY_SEPARATOR)
+        .map(|i| i + LEGACY_SEPARATOR.len())
+        .unwrap_or(0);
+    let mut stream = serde_json::Deserializer::from_str(&content[start..]).into_iter::<VanityBatch>();
+    let mut end = start;
+    loop {
+        match stream.next() {
+            None => return Ok(content.len()),
+            Some(Ok(_)) => end = start + stream.byte_offset(),
+            // The input ran out mid-batch: that is the half-written tail.
+            // Keep the newline that ended the last complete batch.
+            Some(Err(e)) if e.is_eof() => {
+                let rest = &content[end..];
+                return Ok(end + rest.len() - rest.trim_start().len());
+            }
+            Some(Err(e)) => return Err(e),
+        }
+    }
+}
+
+/// Make a new or renamed directory entry survive a crash, not just the file contents
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> Result<(), VanityFileError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)
+        .and_then(|dir| dir.sync_all())
+        .map_err(VanityFileError::io(parent))
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> Result<(), VanityFileError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanity::VanityKeypair;
+    use crate::vanity_pattern::VanityPattern;
+    use solana_sdk::signature::Keypair;
+
+    fn batch(count: usize) -> VanityBatch {
+        let pattern = VanityPattern::parse("pump").unwrap();
+        VanityBatch::new(&pattern, (0..count).map(|_| VanityKeypair::from(&Keypair::new())).collect())
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("onlypump-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_reads_every_layout() {
+        let (a, b) = (batch(2), batch(1));
+        let pretty = serde_json::to_string_pretty(&a).unwrap();
+        let lines = format!("{}\n{}\n", serde_json::to_string(&a).unwrap(), serde_json::to_string(&b).unwrap());
+        let legacy = format!("{}\n---\n{}\n---\n", pretty, serde_json::to_string_pretty(&b).unwrap());
+        for content in [pretty.as_str(), lines.as_str(), legacy.as_str()] {
+            let counts: Vec<usize> = parse_batches(content).unwrap().iter().map(|b| b.keypairs.len()).collect();
+            assert_eq!(counts, if content == pretty { vec![2] } else { vec![2, 1] });
+        }
+        assert!(parse_batches("{\"suffix\": ").is_err());
+    }
+
+    #[test]
+    fn test_repair_drops_only_a_half_written_tail() {
+        let path = temp_path("repair");
+        append(&path, &batch(2)).unwrap();
+        append(&path, &batch(3)).unwrap();
+        assert_eq!(repair_tail(&path).unwrap(), 0);
+
+        // Simulate dying halfway through the next append
+        let line = serde_json::to_string(&batch(1)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&line.as_bytes()[..line.len() / 2]).unwrap();
+        drop(file);
+        assert!(read_batches(&path).is_err());
+
+        assert_eq!(repair_tail(&path).unwrap(), (line.len() / 2) as u64);
+        append(&path, &batch(4)).unwrap();
+        let counts: Vec<usize> = read_batches(&path).unwrap().iter().map(|b| b.keypairs.len()).collect();
+        assert_eq!(counts, vec![2, 3, 4]);
+
+        // Damage in the middle is not a crash artifact, so nothing is cut
+        std::fs::write(&path, format!("{{\"oops\"\n{}\n", serde_json::to_string(&batch(1)).unwrap())).unwrap();
+        assert!(repair_tail(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let path = temp_path("atomic");
+        write_atomic(&path, &batch(2)).unwrap();
+        write_atomic(&path, &batch(5)).unwrap();
+        assert_eq!(read_batches(&path).unwrap()[0].keypairs.len(), 5);
+        assert!(!path.with_file_name(format!("{}.tmp", path.file_name().unwrap().to_string_lossy())).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
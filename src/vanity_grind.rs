@@ -0,0 +1,302 @@
+use crate::vanity_pattern::{PatternKind, VanityPattern, BASE58_ALPHABET, MAX_ADDRESS_LEN};
+use ed25519_dalek::{PublicKey, SecretKey};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::Serialize;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Seeds derived per call to [`Grinder::next_batch`]
+pub const GRIND_BATCH_SIZE: usize = 4_096;
+
+/// Longest suffix whose residue `key mod 58^k` still fits the u128 arithmetic below
+const MAX_FAST_SUFFIX_LEN: usize = 20;
+
+/// Pattern compiled for the grinding hot loop.
+///
+/// Suffix patterns are checked without base58-encoding the key: the last `k` base58 digits of
+/// a 32-byte key are just `key mod 58^k`, so one pass over the bytes yields them directly.
+/// Every other pattern is encoded into a stack buffer and matched as a string.
+pub struct PatternMatcher {
+    pattern: VanityPattern,
+    /// Allowed digit values for each trailing position, last character first
+    suffix_digits: Option<Vec<[bool; 58]>>,
+    modulus: u128,
+}
+
+impl PatternMatcher {
+    pub fn new(pattern: &VanityPattern) -> Self {
+        let suffix_digits = match pattern.kind() {
+            PatternKind::Suffix(suffix) if suffix.len() <= MAX_FAST_SUFFIX_LEN => Some(
+                suffix
+                    .chars()
+                    .rev()
+                    .map(|ch| allowed_digits(ch, pattern.is_case_insensitive()))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        let modulus = suffix_digits
+            .as_ref()
+            .map(|digits| 58u128.pow(digits.len() as u32))
+            .unwrap_or(0);
+
+        Self {
+            pattern: pattern.clone(),
+            suffix_digits,
+            modulus,
+        }
+    }
+
+    pub fn pattern(&self) -> &VanityPattern {
+        &self.pattern
+    }
+
+    /// Check a raw 32-byte public key against the pattern
+    #[inline]
+    pub fn matches_pubkey(&self, pubkey: &[u8; 32]) -> bool {
+        match &self.suffix_digits {
+            Some(digits) => {
+                let mut residue = 0u128;
+                for byte in pubkey {
+                    residue = ((residue << 8) | *byte as u128) % self.modulus;
+                }
+                for allowed in digits {
+                    if !allowed[(residue % 58) as usize] {
+                        return false;
+                    }
+                    residue /= 58;
+                }
+                true
+            }
+            None => {
+                let mut buf = [0u8; MAX_ADDRESS_LEN];
+                match bs58::encode(pubkey).onto(&mut buf[..]) {
+                    // The encoder only ever writes base58 alphabet bytes
+                    Ok(len) => self.pattern.matches(std::str::from_utf8(&buf[..len]).unwrap_or_default()),
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+}
+
+/// Per-thread grinding state: a CSPRNG for seeds and a reusable seed buffer.
+///
+/// Each attempt costs one SHA-512 and one base-point multiplication; a [`Keypair`] is only
+/// built for seeds whose public key matches.
+pub struct Grinder<'a> {
+    matcher: &'a PatternMatcher,
+    rng: StdRng,
+    seeds: Vec<[u8; 32]>,
+}
+
+impl<'a> Grinder<'a> {
+    pub fn new(matcher: &'a PatternMatcher) -> Self {
+        Self {
+            matcher,
+            rng: StdRng::from_entropy(),
+            seeds: vec![[0u8; 32]; GRIND_BATCH_SIZE],
+        }
+    }
+
+    /// Try [`GRIND_BATCH_SIZE`] fresh seeds, pushing every matching keypair onto `hits`.
+    /// Returns the number of attempts made.
+    pub fn next_batch(&mut self, hits: &mut Vec<Keypair>) -> usize {
+        for seed in self.seeds.iter_mut() {
+            self.rng.fill_bytes(seed);
+        }
+
+        for seed in &self.seeds {
+            let secret = SecretKey::from_bytes(seed).expect("seed is 32 bytes");
+            let public = PublicKey::from(&secret);
+            if self.matcher.matches_pubkey(public.as_bytes()) {
+                let keypair = Keypair::new_from_array(*seed);
+                // The arithmetic check ignores leading-zero bytes, so confirm on the real encoding
+                if self.matcher.pattern.matches(&keypair.pubkey().to_string()) {
+                    hits.push(keypair);
+                }
+            }
+        }
+
+        self.seeds.len()
+    }
+}
+
+/// Grind on `threads` threads until one keypair matches or `cancel` is set,
+/// adding every attempt to `attempts` as it happens
+pub fn grind_one(matcher: &PatternMatcher, threads: usize, cancel: &AtomicBool, attempts: &AtomicU64) -> Option<Keypair> {
+    let found: Mutex<Option<Keypair>> = Mutex::new(None);
+    let done = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                let mut grinder = Grinder::new(matcher);
+                let mut hits = Vec::new();
+                while !done.load(Ordering::Relaxed) && !cancel.load(Ordering::Relaxed) {
+                    let tried = grinder.next_batch(&mut hits);
+                    attempts.fetch_add(tried as u64, Ordering::Relaxed);
+                    if let Some(keypair) = hits.pop() {
+                        found.lock().unwrap().get_or_insert(keypair);
+                        done.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap()
+}
+
+/// Attempt and hit counters shared by every worker of one grind toward `target` hits
+pub struct GrindProgress {
+    attempts: AtomicU64,
+    hits: AtomicU64,
+    target: u64,
+    started: Instant,
+}
+
+/// Point-in-time view of a [`GrindProgress`]
+#[derive(Serialize, Clone, Debug)]
+pub struct GrindReport {
+    pub attempts: u64,
+    pub hits: u64,
+    pub target: u64,
+    pub attempts_per_sec: f64,
+    pub expected_attempts: f64,
+    /// Expected seconds until `target` is reached at the current rate (attempts are memoryless)
+    pub eta_secs: Option<f64>,
+    pub elapsed_secs: f64,
+}
+
+impl GrindProgress {
+    pub fn new(target: u64) -> Self {
+        Self {
+            attempts: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            target,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn record(&self, attempts: usize, hits: usize) {
+        self.attempts.fetch_add(attempts as u64, Ordering::Relaxed);
+        self.hits.fetch_add(hits as u64, Ordering::Relaxed);
+    }
+
+    /// `expected_attempts` is the pattern's expected attempts per hit
+    pub fn report(&self, expected_attempts: f64) -> GrindReport {
+        let attempts = self.attempts.load(Ordering::Relaxed);
+        let hits = self.hits.load(Ordering::Relaxed);
+        let elapsed_secs = self.started.elapsed().as_secs_f64();
+        let attempts_per_sec = if elapsed_secs > 0.0 { attempts as f64 / elapsed_secs } else { 0.0 };
+        let remaining = self.target.saturating_sub(hits);
+        let eta_secs = if remaining == 0 {
+            Some(0.0)
+        } else if attempts_per_sec > 0.0 {
+            Some(remaining as f64 * expected_attempts / attempts_per_sec)
+        } else {
+            None
+        };
+
+        GrindReport {
+            attempts,
+            hits,
+            target: self.target,
+            attempts_per_sec,
+            expected_attempts,
+            eta_secs,
+            elapsed_secs,
+        }
+    }
+}
+
+/// Which base58 digit values satisfy `ch` at one position
+fn allowed_digits(ch: char, case_insensitive: bool) -> [bool; 58] {
+    let mut allowed = [false; 58];
+    for (digit, candidate) in BASE58_ALPHABET.chars().enumerate() {
+        allowed[digit] = if case_insensitive {
+            candidate.eq_ignore_ascii_case(&ch)
+        } else {
+            candidate == ch
+        };
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher_agrees_with_string_encoding() {
+        let patterns = ["a", "mp", "(?i)Z", "B*", "*x*"];
+        for pattern in patterns {
+            let pattern = VanityPattern::parse(pattern).unwrap();
+            let matcher = PatternMatcher::new(&pattern);
+            for _ in 0..2_000 {
+                let keypair = Keypair::new();
+                let pubkey = keypair.pubkey().to_bytes();
+                assert_eq!(
+                    matcher.matches_pubkey(&pubkey),
+                    pattern.matches(&keypair.pubkey().to_string()),
+                    "pattern {} disagrees on {}",
+                    pattern,
+                    keypair.pubkey()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_grind_one_counts_attempts_and_honours_cancel() {
+        let matcher = PatternMatcher::new(&VanityPattern::parse("b").unwrap());
+        let attempts = AtomicU64::new(0);
+        let keypair = grind_one(&matcher, 2, &AtomicBool::new(false), &attempts).expect("a hit");
+        assert!(keypair.pubkey().to_string().ends_with('b'));
+        assert!(attempts.load(Ordering::Relaxed) >= GRIND_BATCH_SIZE as u64);
+
+        let cancelled = AtomicBool::new(true);
+        let impossible = PatternMatcher::new(&VanityPattern::parse("zzzzzzzzzz").unwrap());
+        assert!(grind_one(&impossible, 1, &cancelled, &AtomicU64::new(0)).is_none());
+    }
+
+    #[test]
+    fn test_grinder_hits_derive_their_public_key() {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let matcher = PatternMatcher::new(&pattern);
+        let mut grinder = Grinder::new(&matcher);
+        let mut hits = Vec::new();
+        while hits.is_empty() {
+            grinder.next_batch(&mut hits);
+        }
+
+        for keypair in hits {
+            assert!(keypair.pubkey().to_string().ends_with('a'));
+            let roundtrip = Keypair::try_from(keypair.to_bytes().as_slice()).unwrap();
+            assert_eq!(roundtrip.pubkey(), keypair.pubkey());
+        }
+    }
+
+    #[test]
+    fn test_grind_progress_report() {
+        let progress = GrindProgress::new(10);
+        let report = progress.report(100.0);
+        assert_eq!((report.attempts, report.hits, report.target), (0, 0, 10));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        progress.record(4_000, 2);
+        progress.record(4_000, 2);
+        let report = progress.report(1_000.0);
+        assert_eq!((report.attempts, report.hits), (8_000, 4));
+        assert!(report.attempts_per_sec > 0.0);
+        // Six hits to go at ~1000 attempts each
+        let eta = report.eta_secs.unwrap();
+        assert!((eta - 6_000.0 / report.attempts_per_sec).abs() < 1e-6 * eta.max(1.0));
+
+        progress.record(0, 6);
+        assert_eq!(progress.report(1_000.0).eta_secs, Some(0.0));
+    }
+}
@@ -0,0 +1,311 @@
+//! Keypairs pushed to `POST /vanity/ingest` by remote grinders (`generate_pump work`).
+//!
+//! Every worker shares its own 32-byte key with the server. A batch travels as
+//! AES-256-GCM ciphertext bound to the worker id, so private keys stay secret even
+//! where TLS ends early, and a batch that decrypts can only come from that worker.
+
+use crate::metrics::metrics;
+use crate::vanity::VanityKeypair;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Instant;
+use thiserror::Error;
+
+pub const WORKER_KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("unknown worker '{0}'")]
+    UnknownWorker(String),
+    #[error("batch did not decrypt with the worker's key")]
+    Decrypt,
+    #[error("malformed batch: {0}")]
+    Malformed(String),
+    #[error("unknown vanity pool '{0}'")]
+    UnknownPool(String),
+}
+
+/// A worker id and the key it shares with the server
+#[derive(Clone)]
+pub struct WorkerKey {
+    pub id: String,
+    key: [u8; WORKER_KEY_LEN],
+}
+
+// Keys never end up in logs or the printed configuration
+impl fmt::Debug for WorkerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+impl WorkerKey {
+    /// `key` is base64 of 32 random bytes, e.g. from `openssl rand -base64 32`
+    pub fn new(id: &str, key: &str) -> Result<Self, String> {
+        if id.is_empty() {
+            return Err("worker id must not be empty".to_string());
+        }
+        let bytes = BASE64
+            .decode(key.trim())
+            .map_err(|_| format!("key for worker '{}' is not valid base64", id))?;
+        let key = bytes
+            .try_into()
+            .map_err(|_| format!("key for worker '{}' must be {} bytes", id, WORKER_KEY_LEN))?;
+        Ok(Self { id: id.to_string(), key })
+    }
+
+    /// Parse `id:key,id:key`
+    pub fn parse_list(spec: &str) -> Result<Vec<WorkerKey>, String> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (id, key) = entry
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid worker '{}', expected id:base64-key", entry.split(':').next().unwrap_or("")))?;
+                Self::new(id.trim(), key)
+            })
+            .collect()
+    }
+
+    pub fn seal(&self, batch: &IngestBatch) -> Result<IngestEnvelope, IngestError> {
+        let plaintext = serde_json::to_vec(batch).map_err(|e| IngestError::Malformed(e.to_string()))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: self.id.as_bytes() })
+            .map_err(|_| IngestError::Malformed("encryption failed".to_string()))?;
+
+        Ok(IngestEnvelope {
+            worker: self.id.clone(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    pub fn open(&self, envelope: &IngestEnvelope) -> Result<IngestBatch, IngestError> {
+        let nonce = BASE64.decode(&envelope.nonce).map_err(|_| IngestError::Malformed("nonce is not base64".to_string()))?;
+        let ciphertext = BASE64
+            .decode(&envelope.ciphertext)
+            .map_err(|_| IngestError::Malformed("ciphertext is not base64".to_string()))?;
+        if nonce.len() != NONCE_LEN {
+            return Err(IngestError::Malformed(format!("nonce must be {} bytes", NONCE_LEN)));
+        }
+
+        // The worker id is authenticated too, so one worker's batch cannot be replayed as another's
+        let plaintext = self
+            .cipher()
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: envelope.worker.as_bytes() })
+            .map_err(|_| IngestError::Decrypt)?;
+        serde_json::from_slice(&plaintext).map_err(|e| IngestError::Malformed(e.to_string()))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new_from_slice(&self.key).expect("worker keys are 32 bytes")
+    }
+}
+
+/// What a worker sends, before encryption
+#[derive(Serialize, Deserialize)]
+pub struct IngestBatch {
+    pub pool: String,
+    pub keypairs: Vec<VanityKeypair>,
+    /// Keys the worker ground to find this batch, for throughput tracking
+    pub attempts: u64,
+    pub elapsed_secs: f64,
+}
+
+/// Body of `POST /vanity/ingest`
+#[derive(Serialize, Deserialize)]
+pub struct IngestEnvelope {
+    pub worker: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Response to `POST /vanity/ingest`
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct IngestOutcome {
+    pub accepted: usize,
+    /// Already in the pool or handed out before
+    pub duplicates: usize,
+    /// Private key does not derive the address, or the address does not match the pool
+    pub invalid: usize,
+    /// Valid but turned away because the pool was full
+    pub surplus: usize,
+    pub pool_size: usize,
+    pub target_size: usize,
+}
+
+/// Per-worker totals reported by `GET /vanity/stats`
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkerStats {
+    pub id: String,
+    pub batches: u64,
+    pub accepted: u64,
+    pub duplicates: u64,
+    pub invalid: u64,
+    pub surplus: u64,
+    pub attempts: u64,
+    /// Grinding rate over the worker's most recent batch
+    pub attempts_per_sec: f64,
+    pub last_seen_secs: Option<f64>,
+}
+
+struct WorkerState {
+    stats: WorkerStats,
+    last_seen: Option<Instant>,
+}
+
+/// Worker keys plus what each worker has delivered since the server started
+pub struct VanityIngest {
+    keys: HashMap<String, WorkerKey>,
+    workers: Mutex<BTreeMap<String, WorkerState>>,
+}
+
+impl VanityIngest {
+    pub fn new(keys: Vec<WorkerKey>) -> Self {
+        let workers = keys
+            .iter()
+            .map(|key| {
+                let stats = WorkerStats {
+                    id: key.id.clone(),
+                    batches: 0,
+                    accepted: 0,
+                    duplicates: 0,
+                    invalid: 0,
+                    surplus: 0,
+                    attempts: 0,
+                    attempts_per_sec: 0.0,
+                    last_seen_secs: None,
+                };
+                (key.id.clone(), WorkerState { stats, last_seen: None })
+            })
+            .collect();
+        Self {
+            keys: keys.into_iter().map(|key| (key.id.clone(), key)).collect(),
+            workers: Mutex::new(workers),
+        }
+    }
+
+    /// Authenticate and decrypt a batch
+    pub fn open(&self, envelope: &IngestEnvelope) -> Result<IngestBatch, IngestError> {
+        let key = self
+            .keys
+            .get(&envelope.worker)
+            .ok_or_else(|| IngestError::UnknownWorker(envelope.worker.clone()))?;
+        key.open(envelope)
+    }
+
+    pub fn record(&self, worker: &str, attempts: u64, elapsed_secs: f64, outcome: &IngestOutcome) {
+        let mut workers = self.workers.lock().unwrap();
+        let Some(state) = workers.get_mut(worker) else {
+            return;
+        };
+        let stats = &mut state.stats;
+        stats.batches += 1;
+        stats.accepted += outcome.accepted as u64;
+        stats.duplicates += outcome.duplicates as u64;
+        stats.invalid += outcome.invalid as u64;
+        stats.surplus += outcome.surplus as u64;
+        stats.attempts += attempts;
+        if elapsed_secs > 0.0 {
+            stats.attempts_per_sec = attempts as f64 / elapsed_secs;
+        }
+        state.last_seen = Some(Instant::now());
+
+        let ingested = &metrics().vanity_keys_ingested;
+        for (outcome, count) in [
+            ("accepted", outcome.accepted),
+            ("duplicate", outcome.duplicates),
+            ("invalid", outcome.invalid),
+            ("surplus", outcome.surplus),
+        ] {
+            ingested.with_label_values(&[worker, outcome]).inc_by(count as u64);
+        }
+    }
+
+    pub fn stats(&self) -> Vec<WorkerStats> {
+        self.workers
+            .lock()
+            .unwrap()
+            .values()
+            .map(|state| WorkerStats {
+                last_seen_secs: state.last_seen.map(|seen| seen.elapsed().as_secs_f64()),
+                ..state.stats.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    fn key(id: &str) -> WorkerKey {
+        let mut bytes = [0u8; WORKER_KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        WorkerKey::new(id, &BASE64.encode(bytes)).unwrap()
+    }
+
+    fn batch() -> IngestBatch {
+        IngestBatch {
+            pool: "pump".to_string(),
+            keypairs: vec![VanityKeypair::from(&Keypair::new())],
+            attempts: 1_000,
+            elapsed_secs: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_sealed_batches_only_open_for_their_worker() {
+        let (alice, bob) = (key("alice"), key("bob"));
+        let ingest = VanityIngest::new(vec![alice.clone(), bob.clone()]);
+
+        let sealed = alice.seal(&batch()).unwrap();
+        assert!(!sealed.ciphertext.contains("private_key"));
+        let opened = ingest.open(&sealed).unwrap();
+        assert_eq!(opened.pool, "pump");
+        assert_eq!(opened.keypairs.len(), 1);
+
+        // Claiming to be bob changes the authenticated data, and bob's key is different anyway
+        let relabelled = IngestEnvelope { worker: "bob".to_string(), ..alice.seal(&batch()).unwrap() };
+        assert!(matches!(ingest.open(&relabelled), Err(IngestError::Decrypt)));
+        let forged = key("alice").seal(&batch()).unwrap();
+        assert!(matches!(ingest.open(&forged), Err(IngestError::Decrypt)));
+        let stranger = key("carol").seal(&batch()).unwrap();
+        assert!(matches!(ingest.open(&stranger), Err(IngestError::UnknownWorker(_))));
+    }
+
+    #[test]
+    fn test_worker_key_list_and_stats() {
+        let spec = format!("a:{}, b:{}", BASE64.encode([1u8; 32]), BASE64.encode([2u8; 32]));
+        let keys = WorkerKey::parse_list(&spec).unwrap();
+        assert_eq!(keys.iter().map(|k| k.id.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(!format!("{:?}", keys[0]).contains(&BASE64.encode([1u8; 32])));
+        assert!(WorkerKey::parse_list(&format!("a:{}", BASE64.encode([1u8; 16]))).is_err());
+        assert!(WorkerKey::parse_list("a").is_err());
+
+        let ingest = VanityIngest::new(keys);
+        let outcome = IngestOutcome { accepted: 3, duplicates: 1, ..IngestOutcome::default() };
+        ingest.record("a", 4_000, 2.0, &outcome);
+        ingest.record("nobody", 4_000, 2.0, &outcome);
+        let stats = ingest.stats();
+        assert_eq!((stats[0].batches, stats[0].accepted, stats[0].duplicates), (1, 3, 1));
+        assert_eq!(stats[0].attempts_per_sec, 2_000.0);
+        assert!(stats[0].last_seen_secs.is_some());
+        assert_eq!((stats[1].batches, stats[1].last_seen_secs), (0, None));
+    }
+}
@@ -0,0 +1,487 @@
+use crate::vanity_grind::{grind_one, PatternMatcher};
+use crate::vanity_pattern::{VanityPattern, VanityPatternError};
+use rand::Rng;
+use serde::Serialize;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{RwLock, Semaphore};
+use tracing::{info, warn};
+
+/// Bounds on what a single wallet may ask the grinders to do
+#[derive(Clone, Copy, Debug)]
+pub struct JobLimits {
+    /// Reject patterns expected to need more attempts than this
+    pub max_expected_attempts: f64,
+    /// Queued or running jobs allowed per wallet
+    pub max_jobs_per_wallet: usize,
+    /// Queued or running jobs allowed across all wallets, since wallets cost nothing to make
+    pub max_queued: usize,
+    /// Jobs kept in memory at once, including finished ones whose keypair is not claimed yet
+    pub max_retained: usize,
+    /// Jobs grinding at the same time across all wallets
+    pub max_running: usize,
+    /// Threads each running job may use
+    pub threads_per_job: usize,
+    /// How long finished jobs (and unclaimed keypairs) are kept
+    pub retention: Duration,
+}
+
+impl Default for JobLimits {
+    fn default() -> Self {
+        Self {
+            // Five fixed characters: minutes to hours on a couple of cores
+            max_expected_attempts: 58f64.powi(5),
+            max_jobs_per_wallet: 2,
+            max_queued: 32,
+            max_retained: 256,
+            max_running: 2,
+            threads_per_job: 1,
+            retention: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error(transparent)]
+    InvalidPattern(#[from] VanityPatternError),
+    #[error("pattern needs ~{expected:.0} attempts, above the limit of {max:.0}")]
+    TooDifficult { expected: f64, max: f64 },
+    #[error("wallet already has {0} active vanity jobs")]
+    TooManyJobs(usize),
+    #[error("vanity job queue is full ({0} active jobs)")]
+    QueueFull(usize),
+    #[error("too many vanity jobs held ({0} active or unclaimed)")]
+    StoreFull(usize),
+    #[error("vanity job not found")]
+    NotFound,
+    #[error("vanity job belongs to another wallet")]
+    NotOwner,
+    #[error("vanity job has not produced a keypair")]
+    NotReady,
+    #[error("vanity job keypair was already used")]
+    AlreadyClaimed,
+    #[error("vanity job keypair is in use by another create")]
+    InUse,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Cancelled,
+    /// Keypair checked out by a create that has not finished yet
+    Claiming,
+    Claimed,
+}
+
+impl JobStatus {
+    fn is_active(self) -> bool {
+        matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+struct Job {
+    wallet: String,
+    pattern: VanityPattern,
+    status: JobStatus,
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    attempts: Arc<AtomicU64>,
+    cancel: Arc<AtomicBool>,
+    keypair: Option<Keypair>,
+    public_key: Option<String>,
+}
+
+/// Progress report for one job, as returned by the API. Anyone holding the id can read it,
+/// so it leaves out which wallet the job belongs to.
+#[derive(Serialize, Debug)]
+pub struct JobView {
+    pub id: String,
+    pub pattern: String,
+    pub status: JobStatus,
+    pub attempts: u64,
+    pub attempts_per_sec: f64,
+    pub expected_attempts: f64,
+    /// Expected seconds until a hit at the current rate (attempts are memoryless)
+    pub eta_secs: Option<f64>,
+    pub elapsed_secs: f64,
+    pub public_key: Option<String>,
+}
+
+/// Queue of on-demand vanity grinding jobs whose results are reserved for the requesting wallet
+pub struct VanityJobs {
+    jobs: Arc<RwLock<HashMap<String, Job>>>,
+    slots: Arc<Semaphore>,
+    limits: JobLimits,
+}
+
+impl VanityJobs {
+    pub fn new(limits: JobLimits) -> Self {
+        Self {
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(limits.max_running.max(1))),
+            limits,
+        }
+    }
+
+    /// Validate the pattern against the limits and queue a job for `wallet`
+    pub async fn submit(&self, wallet: &str, pattern: &str) -> Result<JobView, JobError> {
+        let pattern = VanityPattern::parse(pattern)?;
+        let expected = pattern.expected_attempts();
+        if expected > self.limits.max_expected_attempts {
+            return Err(JobError::TooDifficult {
+                expected,
+                max: self.limits.max_expected_attempts,
+            });
+        }
+
+        let id = generate_job_id();
+        let attempts = Arc::new(AtomicU64::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let mut jobs = self.jobs.write().await;
+            self.prune(&mut jobs);
+
+            let active = jobs.values().filter(|j| j.wallet == wallet && j.status.is_active()).count();
+            if active >= self.limits.max_jobs_per_wallet {
+                return Err(JobError::TooManyJobs(active));
+            }
+            let queued = jobs.values().filter(|j| j.status.is_active()).count();
+            if queued >= self.limits.max_queued {
+                return Err(JobError::QueueFull(queued));
+            }
+            if jobs.len() >= self.limits.max_retained {
+                return Err(JobError::StoreFull(jobs.len()));
+            }
+
+            jobs.insert(
+                id.clone(),
+                Job {
+                    wallet: wallet.to_string(),
+                    pattern: pattern.clone(),
+                    status: JobStatus::Queued,
+                    started_at: None,
+                    finished_at: None,
+                    attempts: attempts.clone(),
+                    cancel: cancel.clone(),
+                    keypair: None,
+                    public_key: None,
+                },
+            );
+        }
+
+        info!("Queued vanity job {} for wallet {} (pattern: {}, ~{:.0} attempts)", id, wallet, pattern, expected);
+        self.spawn_worker(id.clone(), pattern, attempts, cancel);
+        self.get(&id).await.ok_or(JobError::NotFound)
+    }
+
+    fn spawn_worker(&self, id: String, pattern: VanityPattern, attempts: Arc<AtomicU64>, cancel: Arc<AtomicBool>) {
+        let jobs = self.jobs.clone();
+        let slots = self.slots.clone();
+        let threads = self.limits.threads_per_job;
+
+        tokio::spawn(async move {
+            let _permit = match slots.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Some(job) = jobs.write().await.get_mut(&id) {
+                job.status = JobStatus::Running;
+                job.started_at = Some(Instant::now());
+            }
+
+            let grind_cancel = cancel.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let matcher = PatternMatcher::new(&pattern);
+                grind_one(&matcher, threads, &grind_cancel, &attempts)
+            })
+            .await
+            .unwrap_or_else(|_| {
+                warn!("Vanity job {} grinding task failed", id);
+                None
+            });
+
+            if let Some(job) = jobs.write().await.get_mut(&id) {
+                job.finished_at = Some(Instant::now());
+                match result {
+                    Some(keypair) if job.status != JobStatus::Cancelled => {
+                        info!("Vanity job {} found {}", id, keypair.pubkey());
+                        job.status = JobStatus::Completed;
+                        job.public_key = Some(keypair.pubkey().to_string());
+                        job.keypair = Some(keypair);
+                    }
+                    _ => job.status = JobStatus::Cancelled,
+                }
+            }
+        });
+    }
+
+    /// Current progress of a job
+    pub async fn get(&self, id: &str) -> Option<JobView> {
+        self.jobs.read().await.get(id).map(|job| view(id, job))
+    }
+
+    /// Stop a queued or running job. Only the wallet that submitted it may cancel.
+    pub async fn cancel(&self, id: &str, wallet: &str) -> Result<JobView, JobError> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.get_mut(id).ok_or(JobError::NotFound)?;
+        if job.wallet != wallet {
+            return Err(JobError::NotOwner);
+        }
+        if job.status.is_active() {
+            job.cancel.store(true, Ordering::Relaxed);
+            job.status = JobStatus::Cancelled;
+            job.finished_at = Some(Instant::now());
+            info!("Cancelled vanity job {}", id);
+        }
+        Ok(view(id, job))
+    }
+
+    /// Cancel every queued or running job and stop new ones from starting.
+    /// The grinding threads notice the cancel flag within one batch.
+    pub async fn shutdown(&self) -> usize {
+        self.slots.close();
+        let mut jobs = self.jobs.write().await;
+        let mut cancelled = 0;
+        for job in jobs.values_mut().filter(|job| job.status.is_active()) {
+            job.cancel.store(true, Ordering::Relaxed);
+            job.status = JobStatus::Cancelled;
+            job.finished_at = Some(Instant::now());
+            cancelled += 1;
+        }
+        cancelled
+    }
+
+    /// Take the job's keypair for `wallet`'s create call. Until the create ends in
+    /// [`VanityJobs::mark_claimed`] or [`VanityJobs::restore`], other creates get
+    /// [`JobError::InUse`], so one keypair can never back two launches.
+    pub async fn checkout(&self, id: &str, wallet: &str) -> Result<Keypair, JobError> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.get_mut(id).ok_or(JobError::NotFound)?;
+        if job.wallet != wallet {
+            return Err(JobError::NotOwner);
+        }
+        match job.status {
+            JobStatus::Claimed => Err(JobError::AlreadyClaimed),
+            JobStatus::Claiming => Err(JobError::InUse),
+            JobStatus::Completed => {
+                let keypair = job.keypair.take().ok_or(JobError::NotReady)?;
+                job.status = JobStatus::Claiming;
+                Ok(keypair)
+            }
+            _ => Err(JobError::NotReady),
+        }
+    }
+
+    /// Record that the checked-out keypair has been used on-chain
+    pub async fn mark_claimed(&self, id: &str) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            job.status = JobStatus::Claimed;
+            job.keypair = None;
+        }
+    }
+
+    /// Hand a checked-out keypair back after a failed create, so the wallet can retry
+    pub async fn restore(&self, id: &str, keypair: Keypair) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            if job.status == JobStatus::Claiming {
+                job.status = JobStatus::Completed;
+                job.keypair = Some(keypair);
+            }
+        }
+    }
+
+    /// Drop jobs past their retention, so unclaimed keypairs leave memory without waiting for a submit
+    pub async fn prune_expired(&self) -> usize {
+        let mut jobs = self.jobs.write().await;
+        let before = jobs.len();
+        self.prune(&mut jobs);
+        before - jobs.len()
+    }
+
+    /// Prune jobs every `every` in the background
+    pub fn spawn_pruning(self: &Arc<Self>, every: Duration) {
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                let pruned = jobs.prune_expired().await;
+                if pruned > 0 {
+                    info!("Dropped {} finished vanity jobs", pruned);
+                }
+            }
+        });
+    }
+
+    /// Drop jobs past their retention, then settled ones (oldest first) while the store is full.
+    /// A job whose keypair is checked out stays until its create ends.
+    fn prune(&self, jobs: &mut HashMap<String, Job>) {
+        let retention = self.limits.retention;
+        jobs.retain(|_, job| match job.finished_at {
+            Some(finished) => job.status == JobStatus::Claiming || finished.elapsed() < retention,
+            None => true,
+        });
+
+        let mut settled: Vec<(Instant, String)> = jobs
+            .iter()
+            .filter(|(_, job)| matches!(job.status, JobStatus::Cancelled | JobStatus::Claimed))
+            .filter_map(|(id, job)| job.finished_at.map(|finished| (finished, id.clone())))
+            .collect();
+        settled.sort();
+        for (_, id) in settled {
+            if jobs.len() < self.limits.max_retained {
+                break;
+            }
+            jobs.remove(&id);
+        }
+    }
+}
+
+fn view(id: &str, job: &Job) -> JobView {
+    let attempts = job.attempts.load(Ordering::Relaxed);
+    let expected_attempts = job.pattern.expected_attempts();
+    let elapsed_secs = match job.started_at {
+        Some(started) => job.finished_at.unwrap_or_else(Instant::now).duration_since(started).as_secs_f64(),
+        None => 0.0,
+    };
+    let attempts_per_sec = if elapsed_secs > 0.0 { attempts as f64 / elapsed_secs } else { 0.0 };
+    let eta_secs = match job.status {
+        JobStatus::Running if attempts_per_sec > 0.0 => Some(expected_attempts / attempts_per_sec),
+        _ => None,
+    };
+
+    JobView {
+        id: id.to_string(),
+        pattern: job.pattern.to_string(),
+        status: job.status,
+        attempts,
+        attempts_per_sec,
+        expected_attempts,
+        eta_secs,
+        elapsed_secs,
+        public_key: job.public_key.clone(),
+    }
+}
+
+fn generate_job_id() -> String {
+    let mut rng = rand::thread_rng();
+    format!("{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_for(jobs: &VanityJobs, id: &str, status: JobStatus) -> JobView {
+        for _ in 0..500 {
+            let job = jobs.get(id).await.unwrap();
+            if job.status == status {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("job {} never reached {:?}", id, status);
+    }
+
+    #[tokio::test]
+    async fn test_job_completes_and_is_reserved_for_wallet() {
+        let jobs = VanityJobs::new(JobLimits::default());
+        let job = jobs.submit("wallet-a", "c").await.unwrap();
+        let done = wait_for(&jobs, &job.id, JobStatus::Completed).await;
+        let public_key = done.public_key.expect("completed job exposes its public key");
+        assert!(public_key.ends_with('c'));
+        assert!(done.attempts > 0);
+
+        assert!(matches!(jobs.checkout(&job.id, "wallet-b").await, Err(JobError::NotOwner)));
+        let keypair = jobs.checkout(&job.id, "wallet-a").await.unwrap();
+        assert_eq!(keypair.pubkey().to_string(), public_key);
+        // A second create with the same job waits on the first one's outcome
+        assert!(matches!(jobs.checkout(&job.id, "wallet-a").await, Err(JobError::InUse)));
+        assert_eq!(jobs.get(&job.id).await.unwrap().public_key.as_deref(), Some(public_key.as_str()));
+
+        // A failed create hands the keypair back for a retry
+        jobs.restore(&job.id, keypair).await;
+        let keypair = jobs.checkout(&job.id, "wallet-a").await.unwrap();
+        assert_eq!(keypair.pubkey().to_string(), public_key);
+
+        jobs.mark_claimed(&job.id).await;
+        assert!(matches!(jobs.checkout(&job.id, "wallet-a").await, Err(JobError::AlreadyClaimed)));
+    }
+
+    #[tokio::test]
+    async fn test_job_limits_and_cancel() {
+        let limits = JobLimits {
+            max_jobs_per_wallet: 1,
+            ..JobLimits::default()
+        };
+        let jobs = VanityJobs::new(limits);
+
+        assert!(matches!(jobs.submit("w", "pumpzz").await, Err(JobError::TooDifficult { .. })));
+        assert!(matches!(jobs.submit("w", "p0").await, Err(JobError::InvalidPattern(_))));
+
+        let job = jobs.submit("w", "zzzz").await.unwrap();
+        assert!(matches!(jobs.submit("w", "a").await, Err(JobError::TooManyJobs(1))));
+        assert!(matches!(jobs.cancel(&job.id, "other").await, Err(JobError::NotOwner)));
+
+        let cancelled = jobs.cancel(&job.id, "w").await.unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert!(matches!(jobs.checkout(&job.id, "w").await, Err(JobError::NotReady)));
+        // Cancelled jobs no longer count against the wallet
+        assert!(jobs.submit("w", "a").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_capped_across_wallets() {
+        let limits = JobLimits {
+            max_queued: 2,
+            max_running: 1,
+            ..JobLimits::default()
+        };
+        let jobs = VanityJobs::new(limits);
+        let first = jobs.submit("w1", "zzzz").await.unwrap();
+        jobs.submit("w2", "zzzz").await.unwrap();
+        assert!(matches!(jobs.submit("w3", "zzzz").await, Err(JobError::QueueFull(2))));
+
+        jobs.cancel(&first.id, "w1").await.unwrap();
+        assert!(jobs.submit("w3", "zzzz").await.is_ok());
+        jobs.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_unclaimed_jobs_are_capped_and_pruned() {
+        let limits = JobLimits {
+            max_retained: 2,
+            retention: Duration::from_secs(1),
+            ..JobLimits::default()
+        };
+        let jobs = VanityJobs::new(limits);
+        let first = jobs.submit("w1", "a").await.unwrap();
+        let second = jobs.submit("w2", "a").await.unwrap();
+        wait_for(&jobs, &first.id, JobStatus::Completed).await;
+        wait_for(&jobs, &second.id, JobStatus::Completed).await;
+        // Finished but unclaimed jobs still hold their keypairs, so they count
+        assert!(matches!(jobs.submit("w3", "a").await, Err(JobError::StoreFull(2))));
+
+        // A claimed job makes room for the next one
+        jobs.checkout(&first.id, "w1").await.unwrap();
+        jobs.mark_claimed(&first.id).await;
+        let third = jobs.submit("w3", "a").await.unwrap();
+        assert!(jobs.get(&first.id).await.is_none());
+
+        wait_for(&jobs, &third.id, JobStatus::Completed).await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(jobs.prune_expired().await, 2);
+        assert!(jobs.get(&second.id).await.is_none());
+    }
+}
@@ -0,0 +1,415 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Characters that can appear in a base58-encoded Solana address
+pub const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Longest base58 encoding of a 32-byte public key
+pub const MAX_ADDRESS_LEN: usize = 44;
+
+/// Leading characters whose odds are worked out exactly; later ones are close to uniform
+const MODELLED_LEADING_CHARS: usize = 2;
+
+/// Prefix that marks a pattern string as case-insensitive, e.g. `(?i)pump`
+const CASE_INSENSITIVE_FLAG: &str = "(?i)";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum VanityPatternError {
+    #[error("vanity pattern is empty")]
+    Empty,
+    #[error("'{ch}' can never appear in a base58 address (pattern '{pattern}')")]
+    InvalidChar { ch: char, pattern: String },
+    #[error("vanity pattern '{0}' is longer than a Solana address")]
+    TooLong(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternKind {
+    Prefix(String),
+    Suffix(String),
+    PrefixSuffix { prefix: String, suffix: String },
+    /// `*` matches any run of characters, `?` matches exactly one
+    Glob(String),
+}
+
+/// A validated description of which addresses count as vanity hits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VanityPattern {
+    kind: PatternKind,
+    case_insensitive: bool,
+}
+
+impl VanityPattern {
+    pub fn prefix(prefix: &str) -> Result<Self, VanityPatternError> {
+        Self::validated(PatternKind::Prefix(prefix.to_string()), false)
+    }
+
+    pub fn suffix(suffix: &str) -> Result<Self, VanityPatternError> {
+        Self::validated(PatternKind::Suffix(suffix.to_string()), false)
+    }
+
+    pub fn prefix_suffix(prefix: &str, suffix: &str) -> Result<Self, VanityPatternError> {
+        Self::validated(
+            PatternKind::PrefixSuffix {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            },
+            false,
+        )
+    }
+
+    pub fn glob(glob: &str) -> Result<Self, VanityPatternError> {
+        Self::validated(PatternKind::Glob(glob.to_string()), false)
+    }
+
+    /// Same pattern, but letters match regardless of case
+    pub fn case_insensitive(self) -> Result<Self, VanityPatternError> {
+        Self::validated(self.kind, true)
+    }
+
+    /// Parse the textual form used in config and on the command line:
+    ///
+    /// - `pump` or `*pump` — suffix
+    /// - `Pony*` — prefix
+    /// - `on*mp` — prefix and suffix
+    /// - `ab?c*` or `*x*y` — glob (`*` any run, `?` any single character)
+    /// - a leading `(?i)` makes any of the above case-insensitive
+    ///
+    /// There is no regex form: a regex cannot be checked against the base58 alphabet or
+    /// given an attempts estimate, and job limits and ETAs rely on both.
+    pub fn parse(pattern: &str) -> Result<Self, VanityPatternError> {
+        let (case_insensitive, body) = match pattern.strip_prefix(CASE_INSENSITIVE_FLAG) {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        let stars = body.matches('*').count();
+        let kind = if body.contains('?') || stars > 1 {
+            PatternKind::Glob(body.to_string())
+        } else if stars == 0 {
+            PatternKind::Suffix(body.to_string())
+        } else if let Some(suffix) = body.strip_prefix('*') {
+            PatternKind::Suffix(suffix.to_string())
+        } else if let Some(prefix) = body.strip_suffix('*') {
+            PatternKind::Prefix(prefix.to_string())
+        } else {
+            let (prefix, suffix) = body.split_once('*').expect("pattern contains one '*'");
+            PatternKind::PrefixSuffix {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            }
+        };
+
+        Self::validated(kind, case_insensitive)
+    }
+
+    fn validated(kind: PatternKind, case_insensitive: bool) -> Result<Self, VanityPatternError> {
+        let pattern = Self { kind, case_insensitive };
+        let literals = pattern.literal_chars();
+        if literals.is_empty() {
+            return Err(VanityPatternError::Empty);
+        }
+        if literals.len() > MAX_ADDRESS_LEN {
+            return Err(VanityPatternError::TooLong(pattern.to_string()));
+        }
+        for ch in literals {
+            if alphabet_matches(ch, case_insensitive) == 0 {
+                return Err(VanityPatternError::InvalidChar {
+                    ch,
+                    pattern: pattern.to_string(),
+                });
+            }
+        }
+        Ok(pattern)
+    }
+
+    pub fn kind(&self) -> &PatternKind {
+        &self.kind
+    }
+
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Check a base58 address against the pattern
+    pub fn matches(&self, address: &str) -> bool {
+        let address = address.as_bytes();
+        match &self.kind {
+            PatternKind::Prefix(prefix) => self.starts_with(address, prefix.as_bytes()),
+            PatternKind::Suffix(suffix) => self.ends_with(address, suffix.as_bytes()),
+            PatternKind::PrefixSuffix { prefix, suffix } => {
+                address.len() >= prefix.len() + suffix.len()
+                    && self.starts_with(address, prefix.as_bytes())
+                    && self.ends_with(address, suffix.as_bytes())
+            }
+            PatternKind::Glob(glob) => glob_match(glob.as_bytes(), address, self.case_insensitive),
+        }
+    }
+
+    /// Expected number of random keypairs to try per hit.
+    ///
+    /// Trailing characters are uniform base58 digits. Leading ones are not: a 32-byte key is
+    /// a number below 2^256, about 0.3 * 58^44, so most addresses are 44 characters long
+    /// and start with '2'..'K', and anything later in the alphabet only leads the shorter
+    /// ones. The first characters of an anchored pattern are therefore weighed by the share
+    /// of keys that actually start with them. Globs are still approximate, as they can
+    /// match at more than one alignment.
+    pub fn expected_attempts(&self) -> f64 {
+        let literals = self.literal_chars();
+        let anchored = self.anchored_len().min(MODELLED_LEADING_CHARS);
+        let options: Vec<Vec<usize>> = literals[..anchored]
+            .iter()
+            .map(|&ch| alphabet_digits(ch, self.case_insensitive))
+            .collect();
+        literals[anchored..]
+            .iter()
+            .map(|&ch| 58.0 / alphabet_matches(ch, self.case_insensitive) as f64)
+            .product::<f64>()
+            / leading_share(&options)
+    }
+
+    /// How many literal characters are pinned to the start of the address
+    fn anchored_len(&self) -> usize {
+        match &self.kind {
+            PatternKind::Suffix(_) => 0,
+            PatternKind::Prefix(prefix) | PatternKind::PrefixSuffix { prefix, .. } => prefix.len(),
+            PatternKind::Glob(glob) => glob.chars().take_while(|c| *c != '*' && *c != '?').count(),
+        }
+    }
+
+    fn literal_chars(&self) -> Vec<char> {
+        match &self.kind {
+            PatternKind::Prefix(s) | PatternKind::Suffix(s) => s.chars().collect(),
+            PatternKind::PrefixSuffix { prefix, suffix } => prefix.chars().chain(suffix.chars()).collect(),
+            PatternKind::Glob(glob) => glob.chars().filter(|c| *c != '*' && *c != '?').collect(),
+        }
+    }
+
+    fn starts_with(&self, address: &[u8], prefix: &[u8]) -> bool {
+        address.len() >= prefix.len() && bytes_eq(&address[..prefix.len()], prefix, self.case_insensitive)
+    }
+
+    fn ends_with(&self, address: &[u8], suffix: &[u8]) -> bool {
+        address.len() >= suffix.len()
+            && bytes_eq(&address[address.len() - suffix.len()..], suffix, self.case_insensitive)
+    }
+}
+
+impl fmt::Display for VanityPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.case_insensitive {
+            f.write_str(CASE_INSENSITIVE_FLAG)?;
+        }
+        match &self.kind {
+            // Plain suffixes keep their historical bare form so existing batch files still match
+            PatternKind::Suffix(suffix) => write!(f, "{}", suffix),
+            PatternKind::Prefix(prefix) => write!(f, "{}*", prefix),
+            PatternKind::PrefixSuffix { prefix, suffix } => write!(f, "{}*{}", prefix, suffix),
+            PatternKind::Glob(glob) => write!(f, "{}", glob),
+        }
+    }
+}
+
+impl FromStr for VanityPattern {
+    type Err = VanityPatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// How many base58 characters `ch` matches (0 means the pattern can never hit)
+fn alphabet_matches(ch: char, case_insensitive: bool) -> usize {
+    if case_insensitive {
+        let lower = ch.to_ascii_lowercase();
+        let upper = ch.to_ascii_uppercase();
+        let mut count = BASE58_ALPHABET.contains(lower) as usize;
+        if upper != lower {
+            count += BASE58_ALPHABET.contains(upper) as usize;
+        }
+        count
+    } else {
+        BASE58_ALPHABET.contains(ch) as usize
+    }
+}
+
+/// Base58 digits (positions in the alphabet) that `ch` matches
+fn alphabet_digits(ch: char, case_insensitive: bool) -> Vec<usize> {
+    BASE58_ALPHABET
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == ch || (case_insensitive && c.eq_ignore_ascii_case(&ch)))
+        .map(|(digit, _)| digit)
+        .collect()
+}
+
+/// Share of random public keys whose address starts with one of the digit sequences
+/// `options` allows (one list of accepted digits per position).
+///
+/// An address of length `len` starting with digits `p` is every key in
+/// `[p * 58^(len - k), (p + 1) * 58^(len - k))` that also encodes to exactly `len`
+/// characters, i.e. lies in `[58^(len - 1), 58^len)` and below 2^256. A leading '1' is
+/// a zero byte, which base58 spells out on its own; what follows it is taken as uniform.
+fn leading_share(options: &[Vec<usize>]) -> f64 {
+    let keys = 2f64.powi(256);
+    let mut share = 0.0;
+    let mut prefixes: Vec<Vec<usize>> = vec![Vec::new()];
+    for accepted in options {
+        prefixes = prefixes
+            .into_iter()
+            .flat_map(|prefix| accepted.iter().map(move |&d| [prefix.as_slice(), &[d]].concat()))
+            .collect();
+    }
+    for prefix in prefixes {
+        if prefix.is_empty() {
+            return 1.0;
+        }
+        if prefix[0] == 0 {
+            share += 1.0 / 256.0 / 58f64.powi(prefix.len() as i32 - 1);
+            continue;
+        }
+        let k = prefix.len() as i32;
+        let value = prefix.iter().fold(0.0, |acc, &d| acc * 58.0 + d as f64);
+        for len in k..=MAX_ADDRESS_LEN as i32 {
+            let span = 58f64.powi(len - k);
+            let lo = (value * span).max(58f64.powi(len - 1));
+            let hi = ((value + 1.0) * span).min(58f64.powi(len)).min(keys);
+            if hi > lo {
+                share += (hi - lo) / keys;
+            }
+        }
+    }
+    share
+}
+
+fn bytes_eq(a: &[u8], b: &[u8], case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn glob_match(glob: &[u8], text: &[u8], case_insensitive: bool) -> bool {
+    let (mut g, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if g < glob.len() && glob[g] == b'*' {
+            backtrack = Some((g, t));
+            g += 1;
+        } else if g < glob.len() && (glob[g] == b'?' || bytes_eq(&glob[g..g + 1], &text[t..t + 1], case_insensitive)) {
+            g += 1;
+            t += 1;
+        } else if let Some((star_g, star_t)) = backtrack {
+            g = star_g + 1;
+            t = star_t + 1;
+            backtrack = Some((star_g, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    glob[g..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern_kinds() {
+        assert_eq!(VanityPattern::parse("pump").unwrap().kind(), &PatternKind::Suffix("pump".into()));
+        assert_eq!(VanityPattern::parse("*pump").unwrap().kind(), &PatternKind::Suffix("pump".into()));
+        assert_eq!(VanityPattern::parse("Pony*").unwrap().kind(), &PatternKind::Prefix("Pony".into()));
+        assert_eq!(
+            VanityPattern::parse("on*mp").unwrap().kind(),
+            &PatternKind::PrefixSuffix { prefix: "on".into(), suffix: "mp".into() }
+        );
+        assert_eq!(VanityPattern::parse("a?c*").unwrap().kind(), &PatternKind::Glob("a?c*".into()));
+
+        let ci = VanityPattern::parse("(?i)pump").unwrap();
+        assert!(ci.is_case_insensitive());
+        assert_eq!(ci.to_string(), "(?i)pump");
+        assert_eq!(VanityPattern::parse("on*mp").unwrap().to_string(), "on*mp");
+    }
+
+    #[test]
+    fn test_rejects_impossible_characters() {
+        for bad in ["p0mp", "Olly", "Ice", "lol", "only", "pu-mp"] {
+            assert!(
+                matches!(VanityPattern::parse(bad), Err(VanityPatternError::InvalidChar { .. })),
+                "{} should be rejected",
+                bad
+            );
+        }
+        assert_eq!(VanityPattern::parse("*"), Err(VanityPatternError::Empty));
+        assert!(VanityPattern::parse(&"a".repeat(45)).is_err());
+
+        // Case-insensitive letters are fine as long as one case exists in the alphabet
+        assert!(VanityPattern::parse("(?i)lol").is_ok());
+        assert!(VanityPattern::parse("(?i)0").is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let addr = "B6DzvsY9yXrPsmzA6rvAyNUMYHyN1q8SHcRi94t7pump";
+        assert!(VanityPattern::parse("pump").unwrap().matches(addr));
+        assert!(!VanityPattern::parse("PUMP").unwrap().matches(addr));
+        assert!(VanityPattern::parse("(?i)PUMP").unwrap().matches(addr));
+        assert!(VanityPattern::parse("B6D*").unwrap().matches(addr));
+        assert!(VanityPattern::parse("B6*mp").unwrap().matches(addr));
+        assert!(!VanityPattern::parse("B7*mp").unwrap().matches(addr));
+        assert!(VanityPattern::parse("B?Dz*t7pump").unwrap().matches(addr));
+        assert!(VanityPattern::parse("*Hy*pump").unwrap().matches(addr));
+        assert!(!VanityPattern::parse("*Hy*pumq").unwrap().matches(addr));
+    }
+
+    #[test]
+    fn test_expected_attempts() {
+        let pump = VanityPattern::parse("pump").unwrap();
+        assert_eq!(pump.expected_attempts(), 58f64.powi(4));
+
+        // 'p', 'u' and 'm' exist in both cases, so each matches 2 of 58 characters
+        let ci = VanityPattern::parse("(?i)pum").unwrap();
+        assert_eq!(ci.expected_attempts(), 29f64.powi(3));
+
+        // Digits have no case, and 'l' only exists upper-case
+        let mixed = VanityPattern::parse("(?i)1l").unwrap();
+        assert_eq!(mixed.expected_attempts(), 58.0 * 58.0);
+    }
+
+    #[test]
+    fn test_expected_attempts_for_leading_characters() {
+        let attempts = |pattern: &str| VanityPattern::parse(pattern).unwrap().expected_attempts();
+        let close = |a: f64, b: f64| (a / b - 1.0).abs() < 1e-9;
+        // Most addresses are 44 characters and start with '2'..'K'; later letters only lead
+        // the ~5% that are shorter
+        assert!(attempts("2*") < 20.0, "{}", attempts("2*"));
+        assert!(attempts("z*") > 58.0 * 15.0, "{}", attempts("z*"));
+        let uniform = 58f64.powi(4);
+        assert!(attempts("Pony*") > uniform * 15.0, "{}", attempts("Pony*"));
+        // Anchored globs are weighed the same way as prefixes
+        assert!(close(attempts("Po?y*"), attempts("Po*") * 58.0));
+        assert!(close(attempts("Pony*mp"), attempts("Pony*") * 58.0 * 58.0));
+
+        // Against real encodings of random 32-byte keys
+        let samples = 200_000;
+        let keys: Vec<String> = (0..samples)
+            .map(|_| bs58::encode(rand::random::<[u8; 32]>()).into_string())
+            .collect();
+        for pattern in ["2*", "Ho*", "P*", "(?i)a*"] {
+            let pattern = VanityPattern::parse(pattern).unwrap();
+            let hits = keys.iter().filter(|key| pattern.matches(key)).count() as f64;
+            let expected = samples as f64 / pattern.expected_attempts();
+            assert!(
+                (hits - expected).abs() < 5.0 * expected.sqrt() + 5.0,
+                "{}: {} hits, expected {:.0}",
+                pattern,
+                hits,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use crate::vanity::VanityService;
+use rand::Rng;
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// A create that failed before anything landed leaves the wallet at least this long to retry
+const RETRY_GRACE: Duration = Duration::from_secs(60);
+
+/// How long a wallet may hold pool keypairs it has not launched with yet
+#[derive(Clone, Copy, Debug)]
+pub struct ReservationLimits {
+    pub ttl: Duration,
+    /// Unexpired reservations allowed per wallet, so one wallet cannot drain a pool
+    pub max_per_wallet: usize,
+}
+
+impl Default for ReservationLimits {
+    fn default() -> Self {
+        Self {
+            // Long enough to fill in the launch modal
+            ttl: Duration::from_secs(10 * 60),
+            max_per_wallet: 2,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReservationError {
+    #[error("unknown vanity pool '{0}'")]
+    UnknownPool(String),
+    #[error("vanity pool '{0}' is empty")]
+    PoolEmpty(String),
+    #[error("wallet already holds {0} vanity reservations")]
+    TooManyReservations(usize),
+    #[error("vanity reservation not found")]
+    NotFound,
+    #[error("vanity reservation belongs to another wallet")]
+    NotOwner,
+    #[error("vanity reservation has expired")]
+    Expired,
+    #[error("vanity reservation is in use by another create")]
+    InUse,
+}
+
+struct Reservation {
+    wallet: String,
+    pool: String,
+    /// Taken while a create has it checked out; such a reservation never expires or goes back
+    keypair: Option<Keypair>,
+    public_key: Pubkey,
+    expires_at: Instant,
+}
+
+impl Reservation {
+    fn is_held(&self, now: Instant) -> bool {
+        self.keypair.is_none() || self.expires_at > now
+    }
+}
+
+/// What the launch modal gets back: the final mint address, never its key
+#[derive(Serialize, Debug)]
+pub struct ReservationView {
+    pub id: String,
+    pub public_key: String,
+    pub pool: String,
+    pub expires_in_secs: f64,
+}
+
+/// Pool keypairs set aside for a wallet between `POST /vanity/reserve` and its create call
+pub struct VanityReservations {
+    reservations: RwLock<HashMap<String, Reservation>>,
+    service: Arc<VanityService>,
+    limits: ReservationLimits,
+}
+
+impl VanityReservations {
+    pub fn new(service: Arc<VanityService>, limits: ReservationLimits) -> Self {
+        Self {
+            reservations: RwLock::new(HashMap::new()),
+            service,
+            limits,
+        }
+    }
+
+    /// Take the next keypair from `pool` (the default pool if `None`) and hold it for `wallet`
+    pub async fn reserve(&self, wallet: &str, pool: Option<&str>) -> Result<ReservationView, ReservationError> {
+        let pool = pool.unwrap_or_else(|| self.service.default_pool_name()).to_string();
+        let mut reservations = self.reservations.write().await;
+        let now = Instant::now();
+        let held = reservations
+            .values()
+            .filter(|r| r.wallet == wallet && r.is_held(now))
+            .count();
+        if held >= self.limits.max_per_wallet {
+            return Err(ReservationError::TooManyReservations(held));
+        }
+
+        let keypair = match self.service.get_next_vanity_from(&pool).await {
+            Ok(Some(keypair)) => keypair,
+            Ok(None) => return Err(ReservationError::PoolEmpty(pool)),
+            Err(_) => return Err(ReservationError::UnknownPool(pool)),
+        };
+        let id = generate_reservation_id();
+        let reservation = Reservation {
+            wallet: wallet.to_string(),
+            pool,
+            public_key: keypair.pubkey(),
+            keypair: Some(keypair),
+            expires_at: now + self.limits.ttl,
+        };
+        info!("Reserved vanity address {} for {}", reservation.public_key, wallet);
+        let view = view(&id, &reservation);
+        reservations.insert(id, reservation);
+        Ok(view)
+    }
+
+    /// Take the reserved keypair for `wallet`'s create call. Until the create ends in
+    /// [`VanityReservations::mark_claimed`] or [`VanityReservations::restore`], other creates
+    /// get [`ReservationError::InUse`] and the reservation neither expires nor goes back to its pool.
+    pub async fn checkout(&self, id: &str, wallet: &str) -> Result<Keypair, ReservationError> {
+        let mut reservations = self.reservations.write().await;
+        let reservation = reservations.get_mut(id).ok_or(ReservationError::NotFound)?;
+        if reservation.wallet != wallet {
+            return Err(ReservationError::NotOwner);
+        }
+        if reservation.keypair.is_none() {
+            return Err(ReservationError::InUse);
+        }
+        if reservation.expires_at <= Instant::now() {
+            return Err(ReservationError::Expired);
+        }
+        reservation.keypair.take().ok_or(ReservationError::InUse)
+    }
+
+    /// Record that the checked-out keypair has been used on-chain, or may have been
+    pub async fn mark_claimed(&self, id: &str) {
+        self.reservations.write().await.remove(id);
+    }
+
+    /// Hand a checked-out keypair back after a create that failed before anything landed
+    pub async fn restore(&self, id: &str, keypair: Keypair) {
+        if let Some(reservation) = self.reservations.write().await.get_mut(id) {
+            if reservation.keypair.is_none() {
+                reservation.keypair = Some(keypair);
+                reservation.expires_at = reservation.expires_at.max(Instant::now() + RETRY_GRACE);
+            }
+        }
+    }
+
+    /// Return the keypairs of expired reservations to their pools
+    pub async fn expire(&self) -> usize {
+        let now = Instant::now();
+        self.take_and_release(|r| !r.is_held(now)).await
+    }
+
+    /// Return every keypair not checked out by a create to its pool, so shutdown flushes them with the rest
+    pub async fn release_all(&self) -> usize {
+        self.take_and_release(|r| r.keypair.is_some()).await
+    }
+
+    /// Expire reservations every `every` in the background
+    pub fn spawn_expiry(self: &Arc<Self>, every: Duration) {
+        let reservations = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                let expired = reservations.expire().await;
+                if expired > 0 {
+                    info!("Returned {} expired vanity reservations to their pools", expired);
+                }
+            }
+        });
+    }
+
+    /// Unexpired reservations per pool, for `/vanity/stats`
+    pub async fn held(&self) -> HashMap<String, usize> {
+        let now = Instant::now();
+        let mut held = HashMap::new();
+        for reservation in self.reservations.read().await.values().filter(|r| r.is_held(now)) {
+            *held.entry(reservation.pool.clone()).or_insert(0) += 1;
+        }
+        held
+    }
+
+    async fn take_and_release(&self, pick: impl Fn(&Reservation) -> bool) -> usize {
+        let picked: Vec<Reservation> = {
+            let mut reservations = self.reservations.write().await;
+            let ids: Vec<String> = reservations
+                .iter()
+                .filter(|(_, r)| pick(r))
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| reservations.remove(id)).collect()
+        };
+        let count = picked.len();
+        for reservation in picked {
+            if let Some(keypair) = reservation.keypair {
+                self.service.release(&reservation.pool, keypair).await;
+            }
+        }
+        count
+    }
+}
+
+fn view(id: &str, reservation: &Reservation) -> ReservationView {
+    ReservationView {
+        id: id.to_string(),
+        public_key: reservation.public_key.to_string(),
+        pool: reservation.pool.clone(),
+        expires_in_secs: reservation.expires_at.saturating_duration_since(Instant::now()).as_secs_f64(),
+    }
+}
+
+fn generate_reservation_id() -> String {
+    let mut rng = rand::thread_rng();
+    format!("{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanity::{PoolSpec, VanityBatch, VanityKeypair, VanityPool};
+    use crate::vanity_pattern::VanityPattern;
+
+    /// A file-seeded pool of `size` keypairs that does not refill itself
+    async fn service(size: usize) -> Arc<VanityService> {
+        let pattern = VanityPattern::parse("a").unwrap();
+        let path = std::env::temp_dir().join(format!("onlypump-reserve-{}.json", Keypair::new().pubkey()));
+        let seed = VanityPool::generate_vanity_batch(&pattern, size);
+        let batch = VanityBatch::new(&pattern, seed.iter().map(VanityKeypair::from).collect());
+        std::fs::write(&path, serde_json::to_string(&batch).unwrap()).unwrap();
+        let spec = PoolSpec {
+            name: "test".to_string(),
+            pattern,
+            pool_size: size,
+            file: Some(path.to_str().unwrap().to_string()),
+        };
+        let service = VanityService::from_specs(vec![spec], None, None, 1, false).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        Arc::new(service)
+    }
+
+    #[tokio::test]
+    async fn test_reservation_is_held_for_its_wallet() {
+        let service = service(3).await;
+        let reservations = VanityReservations::new(service.clone(), ReservationLimits::default());
+
+        let reserved = reservations.reserve("wallet-a", None).await.unwrap();
+        assert_eq!(reserved.pool, "test");
+        assert_eq!(service.pool_size().await, 2);
+        assert!(matches!(reservations.checkout(&reserved.id, "wallet-b").await, Err(ReservationError::NotOwner)));
+        let keypair = reservations.checkout(&reserved.id, "wallet-a").await.unwrap();
+        assert_eq!(keypair.pubkey().to_string(), reserved.public_key);
+        // A second create with the same reservation waits on the first one's outcome
+        assert!(matches!(reservations.checkout(&reserved.id, "wallet-a").await, Err(ReservationError::InUse)));
+        reservations.restore(&reserved.id, keypair).await;
+        let keypair = reservations.checkout(&reserved.id, "wallet-a").await.unwrap();
+        assert_eq!(keypair.pubkey().to_string(), reserved.public_key);
+
+        reservations.reserve("wallet-a", Some("test")).await.unwrap();
+        assert!(matches!(
+            reservations.reserve("wallet-a", None).await,
+            Err(ReservationError::TooManyReservations(2))
+        ));
+        assert!(matches!(reservations.reserve("wallet-b", Some("other")).await, Err(ReservationError::UnknownPool(_))));
+        assert_eq!(reservations.held().await.get("test"), Some(&2));
+
+        reservations.mark_claimed(&reserved.id).await;
+        assert!(matches!(reservations.checkout(&reserved.id, "wallet-a").await, Err(ReservationError::NotFound)));
+        // The claimed key is gone for good; the other one goes back on shutdown
+        assert_eq!(reservations.release_all().await, 1);
+        assert_eq!(service.pool_size().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_reservations_return_to_pool() {
+        let service = service(1).await;
+        let limits = ReservationLimits { ttl: Duration::from_millis(50), ..ReservationLimits::default() };
+        let reservations = VanityReservations::new(service.clone(), limits);
+
+        let reserved = reservations.reserve("wallet-a", None).await.unwrap();
+        assert!(matches!(reservations.reserve("wallet-b", None).await, Err(ReservationError::PoolEmpty(_))));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(matches!(reservations.checkout(&reserved.id, "wallet-a").await, Err(ReservationError::Expired)));
+
+        assert_eq!(reservations.expire().await, 1);
+        assert_eq!(service.pool_size().await, 1);
+        // Back in the pool, the address can be reserved again
+        let again = reservations.reserve("wallet-b", None).await.unwrap();
+        assert_eq!(again.public_key, reserved.public_key);
+    }
+
+    #[tokio::test]
+    async fn test_checked_out_reservation_is_never_returned() {
+        let service = service(1).await;
+        let limits = ReservationLimits { ttl: Duration::from_millis(50), ..ReservationLimits::default() };
+        let reservations = VanityReservations::new(service.clone(), limits);
+
+        let reserved = reservations.reserve("wallet-a", None).await.unwrap();
+        let keypair = reservations.checkout(&reserved.id, "wallet-a").await.unwrap();
+        // A create running past the TTL keeps its key out of the pool, even on shutdown
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(reservations.expire().await, 0);
+        assert_eq!(reservations.release_all().await, 0);
+        assert_eq!(service.pool_size().await, 0);
+        assert_eq!(reservations.held().await.get("test"), Some(&1));
+
+        // Restored after a definite failure, it can be retried before it expires again
+        reservations.restore(&reserved.id, keypair).await;
+        assert_eq!(reservations.expire().await, 0);
+        let keypair = reservations.checkout(&reserved.id, "wallet-a").await.unwrap();
+        assert_eq!(keypair.pubkey().to_string(), reserved.public_key);
+    }
+}